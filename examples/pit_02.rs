@@ -0,0 +1,42 @@
+use ray_tracing_rust::{
+    canvas::Canvas,
+    tuples::{
+        helpers::{color, point, vector},
+        Tuple,
+    },
+};
+
+fn tick(env: (Tuple, Tuple), proj: (Tuple, Tuple)) -> (Tuple, Tuple) {
+    let position = proj.0 + proj.1;
+    let velocity = proj.1 + env.0 + env.1;
+    (position, velocity)
+}
+
+fn main() {
+    let start = point(0, 1, 0);
+    let velocity = vector(1, 1.8, 0).normalized() * 11.25;
+
+    let mut p = (start, velocity);
+
+    let gravity = vector(0, -0.1, 0);
+    let wind = vector(-0.01, 0, 0);
+
+    let e = (gravity, wind);
+
+    let mut c = Canvas::new(900, 550);
+
+    loop {
+        if p.0.y() <= 0.0 {
+            break;
+        }
+
+        let x = p.0.x().round() as usize;
+        let y = 550 - p.0.y().round() as usize;
+
+        c[(x, y)] = color(1, 0, 0);
+
+        p = tick(e, p);
+    }
+
+    c.save("./temp/pit_02.png").expect("Unable to save file");
+}
@@ -0,0 +1,15 @@
+use ray_tracing_rust::matrix::helpers::Mat4;
+
+fn main() {
+    let a: Mat4 = ((3, -9, 7, 2), (3, -8, 2, -9), (-4, 4, 4, 1), (-6, 5, -1, 1)).into();
+    // let a: Mat2 = ((3, -9), (3, -8)).into();
+    println!("{a:?}");
+
+    println!("Invert of identity:\n{:?}", Mat4::identity());
+    println!("A * inv(A) =\n{:?}", a.clone() * a.inverse());
+    println!(
+        "{:?}=\n{:?}",
+        a.inverse().transpose(),
+        a.transpose().inverse()
+    );
+}
@@ -0,0 +1,22 @@
+use ray_tracing_rust::{
+    canvas::Canvas,
+    transformation::rotation_y,
+    tuples::helpers::{colors, point},
+};
+
+fn main() {
+    let mut c = Canvas::new(100, 100);
+
+    for i in 0..12 {
+        let p = point(0, 0, 45);
+        let rot = rotation_y((30.0 * i as f64).to_radians());
+        let np = rot * p;
+
+        let x = 50.0 + np.x();
+        let y = 50.0 + np.z();
+
+        c[(x as usize, y as usize)] = colors::white();
+    }
+
+    c.save("./temp/pit_04.png").unwrap();
+}
@@ -0,0 +1,44 @@
+use ray_tracing_rust::{
+    canvas::Canvas,
+    ray::{Intersections, Ray},
+    shapes::{Shape, Sphere},
+    transformation::scaling,
+    tuples::helpers::{color, point},
+};
+
+fn main() {
+    let ray_origin = point(0, 0, -5);
+    let wall_z = 10.0;
+    let wall_size = 7.0;
+
+    let canvas_pixel = 800;
+
+    let pixel_size = wall_size / canvas_pixel as f64;
+
+    let half = wall_size / 2.0;
+
+    let mut canvas = Canvas::new(canvas_pixel, canvas_pixel);
+    let color = color(1, 0, 0);
+
+    let mut shape = Sphere::new();
+    shape.set_transform(scaling(1.0, 0.5, 1.0));
+
+    for y in 0..canvas_pixel - 1 {
+        let world_y = half - pixel_size * y as f64;
+
+        for x in 0..canvas_pixel - 1 {
+            let world_x = -half + pixel_size * x as f64;
+
+            let position = point(world_x, world_y, wall_z);
+
+            let r = Ray::new(ray_origin, (position - ray_origin).normalized());
+            let xs = shape.intersect(&r);
+
+            if xs.hit().is_some() {
+                canvas[(x, y)] = color;
+            }
+        }
+    }
+
+    canvas.save("./temp/pit_05.png").unwrap();
+}
@@ -0,0 +1,71 @@
+use ray_tracing_rust::{
+    canvas::Canvas,
+    light::PointLight,
+    material::Material,
+    ray::{Intersections, Ray},
+    shapes::{Shape, Sphere},
+    tuples::helpers::{color, point},
+};
+
+macro_rules! time {
+    ($b:block) => {{
+        let xx_timer_bb_dnsajkfdasb = std::time::Instant::now();
+        $b;
+        xx_timer_bb_dnsajkfdasb.elapsed()
+    }};
+}
+
+fn main() {
+    let ray_origin = point(0, 0, -5);
+    let wall_z = 10.0;
+    let wall_size = 7.0;
+
+    let canvas_pixel = 800;
+
+    let pixel_size = wall_size / canvas_pixel as f64;
+
+    let half = wall_size / 2.0;
+
+    let mut canvas = Canvas::new(canvas_pixel, canvas_pixel);
+
+    let mut shape = Sphere::new();
+    // shape.set_transform(scaling(1.0, 0.5, 1.0));
+    let m = Material {
+        color: color(1, 0.2, 1).solid(),
+        ..Default::default()
+    };
+    shape.set_material(m);
+
+    let light = PointLight::new(color(1, 1, 1), point(-10, 10, -10));
+
+    let time = time!({
+        for y in 0..canvas_pixel - 1 {
+            let world_y = half - pixel_size * y as f64;
+
+            for x in 0..canvas_pixel - 1 {
+                let world_x = -half + pixel_size * x as f64;
+
+                let position = point(world_x, world_y, wall_z);
+
+                let r = Ray::new(ray_origin, (position - ray_origin).normalized());
+                let xs = shape.intersect(&r);
+
+                if let Some(hit) = xs.hit() {
+                    let point = r.at(hit.t());
+                    let normal = hit.object().normal_at(point);
+                    let eye = -r.direction();
+
+                    let color = hit
+                        .object()
+                        .material()
+                        .lighting(&shape, &light, point, eye, normal, 1.0);
+
+                    canvas[(x, y)] = color;
+                }
+            }
+        }
+    });
+
+    println!("{:#?}", time);
+    canvas.save("./temp/pit_06.png").unwrap();
+}
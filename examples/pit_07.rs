@@ -0,0 +1,92 @@
+use std::time::Instant;
+
+use ray_tracing_rust::{
+    camera::Camera,
+    light::PointLight,
+    material::Material,
+    shapes::{Shape, Sphere},
+    transformation::{helper::TransformationBuilder, scaling, translation, view_transform, PI},
+    tuples::helpers::{color, point, vector},
+    world::World,
+};
+
+fn main() {
+    let mut floor = Sphere::new();
+    floor.set_transform(scaling(10.0, 0.01, 10.0));
+    floor.material_mut().color = color(1, 0.9, 0.9).solid();
+    floor.material_mut().specular = 0.0;
+
+    let mut left_wall = Sphere::new();
+    left_wall.set_transform(
+        TransformationBuilder::create()
+            .scaling(10.0, 0.01, 10.0)
+            .rotation_x(PI / 2.0)
+            .rotation_y(-PI / 4.0)
+            .translation(0.0, 0.0, 5.0)
+            .build(),
+    );
+    left_wall.set_material(Material::clone(floor.material()));
+
+    let mut right_wall = Sphere::new();
+    right_wall.set_transform(
+        TransformationBuilder::create()
+            .scaling(10.0, 0.01, 10.0)
+            .rotation_x(PI / 2.0)
+            .rotation_y(PI / 4.0)
+            .translation(0.0, 0.0, 5.0)
+            .build(),
+    );
+    right_wall.set_material(Material::clone(floor.material()));
+
+    let mut middle = Sphere::new();
+    middle.set_transform(translation(-0.5, 1.0, 0.5));
+    middle.material_mut().color = color(0.1, 1.0, 0.5).solid();
+    middle.material_mut().diffuse = 0.7;
+    middle.material_mut().specular = 0.3;
+
+    let mut right = Sphere::new();
+    right.set_transform(
+        TransformationBuilder::create()
+            .scaling(0.5, 0.5, 0.5)
+            .translation(1.5, 0.5, -0.5)
+            .build(),
+    );
+    right.material_mut().color = color(0.5, 1.0, 0.1).solid();
+    right.material_mut().diffuse = 0.7;
+    right.material_mut().specular = 0.3;
+
+    let mut left = Sphere::new();
+    left.set_transform(
+        TransformationBuilder::create()
+            .scaling(0.33, 0.33, 0.33)
+            .translation(-1.5, 0.33, -0.75)
+            .build(),
+    );
+    left.material_mut().color = color(1, 0.8, 0.1).solid();
+    left.material_mut().diffuse = 0.7;
+    left.material_mut().specular = 0.3;
+
+    let mut world = World::new();
+    world.objetcs_mut().push(Box::new(floor));
+    world.objetcs_mut().push(Box::new(left_wall));
+    world.objetcs_mut().push(Box::new(right_wall));
+    world.objetcs_mut().push(Box::new(middle));
+    world.objetcs_mut().push(Box::new(left));
+    world.objetcs_mut().push(Box::new(right));
+
+    world.add_light(PointLight::new(color(1, 1, 1), point(-10, 10, -10)));
+
+    let camera = Camera::new_transformed(
+        1920,
+        1080,
+        PI / 3.0,
+        view_transform(point(0, 1.5, -5), point(0, 1, 0), vector(0, 1, 0)),
+    );
+
+    let timer = Instant::now();
+    let canvas = camera.render(&world);
+    let took = timer.elapsed();
+
+    canvas.save("./temp/pit_07.png").unwrap();
+    println!("Rendering took: {:#?}", took);
+}
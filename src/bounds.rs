@@ -0,0 +1,295 @@
+//! Axis-aligned bounding boxes, used to skip shapes a ray cannot hit.
+
+use crate::{matrix::helpers::Mat4, ray::Ray, tuples::Tuple};
+
+/// An axis-aligned box given by its `min` and `max` corners. Components may
+/// be infinite for unbounded shapes like planes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    min: Tuple,
+    max: Tuple,
+}
+
+impl BoundingBox {
+    pub fn new(min: Tuple, max: Tuple) -> BoundingBox {
+        BoundingBox { min, max }
+    }
+
+    /// A box containing nothing; adding a point or box to it yields that.
+    pub fn empty() -> BoundingBox {
+        let inf = f64::INFINITY;
+        BoundingBox::new(Tuple::point(inf, inf, inf), Tuple::point(-inf, -inf, -inf))
+    }
+
+    pub fn infinite() -> BoundingBox {
+        let inf = f64::INFINITY;
+        BoundingBox::new(Tuple::point(-inf, -inf, -inf), Tuple::point(inf, inf, inf))
+    }
+
+    pub fn from_points<I: IntoIterator<Item = Tuple>>(points: I) -> BoundingBox {
+        let mut b = BoundingBox::empty();
+        for p in points {
+            b.add_point(p);
+        }
+        b
+    }
+
+    pub fn min(&self) -> Tuple {
+        self.min
+    }
+    pub fn max(&self) -> Tuple {
+        self.max
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x() > self.max.x() || self.min.y() > self.max.y() || self.min.z() > self.max.z()
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|p| p.x().is_finite() && p.y().is_finite() && p.z().is_finite())
+    }
+
+    pub fn add_point(&mut self, p: Tuple) {
+        self.min = Tuple::point(
+            self.min.x().min(p.x()),
+            self.min.y().min(p.y()),
+            self.min.z().min(p.z()),
+        );
+        self.max = Tuple::point(
+            self.max.x().max(p.x()),
+            self.max.y().max(p.y()),
+            self.max.z().max(p.z()),
+        );
+    }
+
+    pub fn merge(&mut self, other: &BoundingBox) {
+        if !other.is_empty() {
+            self.add_point(other.min);
+            self.add_point(other.max);
+        }
+    }
+
+    pub fn contains_point(&self, p: Tuple) -> bool {
+        (self.min.x()..=self.max.x()).contains(&p.x())
+            && (self.min.y()..=self.max.y()).contains(&p.y())
+            && (self.min.z()..=self.max.z()).contains(&p.z())
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    pub fn centroid(&self) -> Tuple {
+        Tuple::point(
+            (self.min.x() + self.max.x()) / 2.0,
+            (self.min.y() + self.max.y()) / 2.0,
+            (self.min.z() + self.max.z()) / 2.0,
+        )
+    }
+
+    /// Grows the box by `amount` in every direction.
+    pub fn padded(&self, amount: f64) -> BoundingBox {
+        let d = Tuple::vector(amount, amount, amount);
+        BoundingBox::new(self.min - d, self.max + d)
+    }
+
+    /// The box containing this box after applying `m`.
+    ///
+    /// Computed per axis rather than from the eight corners, so infinite
+    /// extents stay infinite only along the axes they end up on.
+    pub fn transform(&self, m: &Mat4) -> BoundingBox {
+        if self.is_empty() {
+            return *self;
+        }
+
+        let min = [self.min.x(), self.min.y(), self.min.z()];
+        let max = [self.max.x(), self.max.y(), self.max.z()];
+        let mut new_min = [0.0; 3];
+        let mut new_max = [0.0; 3];
+
+        for row in 0..3 {
+            let (mut lo, mut hi) = (m[(row, 3)], m[(row, 3)]);
+            for col in 0..3 {
+                let a = m[(row, col)];
+                if a == 0.0 {
+                    continue;
+                }
+                let (p, q) = (a * min[col], a * max[col]);
+                lo += p.min(q);
+                hi += p.max(q);
+            }
+            new_min[row] = lo;
+            new_max[row] = hi;
+        }
+
+        BoundingBox::new(
+            Tuple::point(new_min[0], new_min[1], new_min[2]),
+            Tuple::point(new_max[0], new_max[1], new_max[2]),
+        )
+    }
+
+    /// Whether the line through `ray` passes through the box. Hits behind the
+    /// ray origin count too, since shapes report those as well.
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let (o, d) = (ray.origin(), ray.direction());
+        let axes = [
+            (o.x(), d.x(), self.min.x(), self.max.x()),
+            (o.y(), d.y(), self.min.y(), self.max.y()),
+            (o.z(), d.z(), self.min.z(), self.max.z()),
+        ];
+
+        let mut tmin = f64::NEG_INFINITY;
+        let mut tmax = f64::INFINITY;
+        for (origin, direction, min, max) in axes {
+            let (t0, t1) = slab(origin, direction, min, max);
+            tmin = tmin.max(t0);
+            tmax = tmax.min(t1);
+        }
+
+        tmin <= tmax
+    }
+}
+
+/// The distances, nearest first, at which a ray along one axis enters and
+/// leaves the slab `min..=max`. Only an exactly parallel ray is special
+/// cased: it is inside the slab everywhere or nowhere. Tiny directions give
+/// huge (or infinite) distances, as they should.
+pub(crate) fn slab(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    if direction == 0.0 {
+        return if origin < min || origin > max {
+            (f64::INFINITY, f64::NEG_INFINITY)
+        } else {
+            (f64::NEG_INFINITY, f64::INFINITY)
+        };
+    }
+    let t0 = (min - origin) / direction;
+    let t1 = (max - origin) / direction;
+    if t0 > t1 {
+        (t1, t0)
+    } else {
+        (t0, t1)
+    }
+}
+
+impl Default for BoundingBox {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::Ray,
+        transformation::{rotation_x, rotation_y, translation, PI},
+        tuples::helpers::{point, vector},
+    };
+
+    use super::BoundingBox;
+
+    #[test]
+    fn empty_box() {
+        let b = BoundingBox::empty();
+        assert!(b.is_empty());
+        assert!(!b.contains_point(point(0, 0, 0)));
+    }
+
+    #[test]
+    fn adding_points() {
+        let mut b = BoundingBox::empty();
+        b.add_point(point(-5, 2, 0));
+        b.add_point(point(7, 0, -3));
+        assert_eq!(b.min(), point(-5, 0, -3));
+        assert_eq!(b.max(), point(7, 2, 0));
+    }
+
+    #[test]
+    fn merging_boxes() {
+        let mut b1 = BoundingBox::new(point(-5, -2, 0), point(7, 4, 4));
+        let b2 = BoundingBox::new(point(8, -7, -2), point(14, 2, 8));
+        b1.merge(&b2);
+        assert_eq!(b1.min(), point(-5, -7, -2));
+        assert_eq!(b1.max(), point(14, 4, 8));
+    }
+
+    #[test]
+    fn containment() {
+        let b = BoundingBox::new(point(5, -2, 0), point(11, 4, 7));
+        assert!(b.contains_point(point(5, -2, 0)));
+        assert!(b.contains_point(point(8, 1, 3)));
+        assert!(!b.contains_point(point(3, 0, 3)));
+        assert!(!b.contains_point(point(8, 1, 8)));
+
+        assert!(b.contains_box(&BoundingBox::new(point(6, -1, 1), point(10, 3, 6))));
+        assert!(!b.contains_box(&BoundingBox::new(point(4, -3, -1), point(10, 3, 6))));
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn transforming_box() {
+        let b = BoundingBox::new(point(-1, -1, -1), point(1, 1, 1));
+        let b2 = b.transform(&(rotation_x(PI / 4.0) * rotation_y(PI / 4.0)));
+        assert_eq!(b2.min(), point(-1.41421, -1.70711, -1.70711));
+        assert_eq!(b2.max(), point(1.41421, 1.70711, 1.70711));
+    }
+
+    #[test]
+    fn transforming_infinite_box() {
+        let inf = f64::INFINITY;
+        let b = BoundingBox::new(point(-inf, 0, -inf), point(inf, 0, inf));
+        let moved = b.transform(&translation(0.0, 2.0, 0.0));
+        assert_eq!(moved.min().y(), 2.0);
+        assert_eq!(moved.max().y(), 2.0);
+        assert_eq!(moved.max().x(), inf);
+        assert!(!moved.is_finite());
+
+        let tilted = b.transform(&rotation_x(PI / 4.0));
+        assert_eq!(tilted.min().y(), -inf);
+        assert_eq!(tilted.max().x(), inf);
+    }
+
+    #[test]
+    fn ray_intersecting_box() {
+        let b = BoundingBox::new(point(5, -2, 0), point(11, 4, 7));
+        let cases = [
+            (point(15, 1, 2), vector(-1, 0, 0), true),
+            (point(-5, -1, 4), vector(1, 0, 0), true),
+            (point(7, 6, 5), vector(0, -1, 0), true),
+            (point(9, -5, 6), vector(0, 1, 0), true),
+            (point(8, 2, 12), vector(0, 0, -1), true),
+            (point(6, 0, -5), vector(0, 0, 1), true),
+            (point(8, 1, 3.5), vector(0, 0, 1), true),
+            (point(9, -1, -8), vector(2, 4, 6), false),
+            (point(8, 3, -4), vector(6, 2, 4), false),
+            (point(9, -1, -2), vector(4, 6, 2), false),
+            (point(4, 0, 9), vector(0, 0, -1), false),
+            (point(8, 6, -1), vector(0, -1, 0), false),
+            (point(12, 5, 4), vector(-1, 0, 0), false),
+        ];
+        for (origin, direction, expected) in cases {
+            let r = Ray::new(origin, direction.normalized());
+            assert_eq!(b.intersects(&r), expected, "{origin:?} {direction:?}");
+        }
+    }
+
+    #[test]
+    fn ray_behind_box_still_intersects_line() {
+        let b = BoundingBox::new(point(-1, -1, -1), point(1, 1, 1));
+        let r = Ray::new(point(0, 0, 5), vector(0, 0, 1));
+        assert!(b.intersects(&r));
+    }
+
+    #[test]
+    fn ray_against_infinite_box() {
+        let inf = f64::INFINITY;
+        let b = BoundingBox::new(point(-inf, 0, -inf), point(inf, 0, inf));
+        assert!(b.intersects(&Ray::new(point(3, 5, 1), vector(0, -1, 0))));
+        assert!(!b.intersects(&Ray::new(point(3, 5, 1), vector(1, 0, 0))));
+    }
+}
@@ -0,0 +1,121 @@
+//! A bounding volume hierarchy over a list of bounding boxes, used by
+//! `World`, `Group` and `Mesh` to find the shapes a ray may hit.
+
+use crate::{bounds::BoundingBox, ray::Ray, tuples::EPSILON};
+
+/// Leaves hold at most this many items.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug)]
+enum Node {
+    Leaf(Vec<usize>),
+    Branch(usize, usize),
+}
+
+/// Item bounds are padded by `EPSILON` so hits right on the surface of a
+/// flat box (like the one of an axis-aligned triangle) aren't lost.
+#[derive(Debug)]
+pub struct Bvh {
+    items: Vec<BoundingBox>,
+    bounds: Vec<BoundingBox>,
+    nodes: Vec<Node>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    /// Builds a hierarchy over `bounds`; the items are referred to by their
+    /// index in that slice. Items with infinite bounds are always candidates.
+    pub fn build(bounds: &[BoundingBox]) -> Bvh {
+        let mut bvh = Bvh {
+            items: bounds.iter().map(|b| b.padded(EPSILON)).collect(),
+            bounds: Vec::new(),
+            nodes: Vec::new(),
+            unbounded: Vec::new(),
+        };
+
+        let mut items = Vec::new();
+        for (i, b) in bounds.iter().enumerate() {
+            if b.is_finite() {
+                items.push((i, bvh.items[i]));
+            } else if !b.is_empty() {
+                bvh.unbounded.push(i);
+            }
+        }
+
+        if !items.is_empty() {
+            bvh.build_node(items);
+        }
+        bvh
+    }
+
+    fn build_node(&mut self, mut items: Vec<(usize, BoundingBox)>) -> usize {
+        let mut bounds = BoundingBox::empty();
+        let mut centroids = BoundingBox::empty();
+        for (_, b) in &items {
+            bounds.merge(b);
+            centroids.add_point(b.centroid());
+        }
+
+        let index = self.nodes.len();
+        self.bounds.push(bounds);
+        self.nodes.push(Node::Leaf(Vec::new()));
+
+        let extent = centroids.max() - centroids.min();
+        let axis = if extent.x() >= extent.y() && extent.x() >= extent.z() {
+            0
+        } else if extent.y() >= extent.z() {
+            1
+        } else {
+            2
+        };
+        let component = |b: &BoundingBox| {
+            let c = b.centroid();
+            [c.x(), c.y(), c.z()][axis]
+        };
+
+        if items.len() <= LEAF_SIZE || [extent.x(), extent.y(), extent.z()][axis] <= 0.0 {
+            self.nodes[index] = Node::Leaf(items.into_iter().map(|(i, _)| i).collect());
+            return index;
+        }
+
+        items.sort_by(|(_, a), (_, b)| component(a).total_cmp(&component(b)));
+        let right_items = items.split_off(items.len() / 2);
+        let left = self.build_node(items);
+        let right = self.build_node(right_items);
+        self.nodes[index] = Node::Branch(left, right);
+        index
+    }
+
+    /// The indices of all items whose bounds `ray` passes through, in
+    /// ascending order so callers see them in the same order as without
+    /// the hierarchy.
+    pub fn candidates(&self, ray: &Ray) -> Vec<usize> {
+        let mut result = self.unbounded.clone();
+
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(node) = stack.pop() {
+            if !self.bounds[node].intersects(ray) {
+                continue;
+            }
+            match &self.nodes[node] {
+                Node::Leaf(items) => result.extend(
+                    items
+                        .iter()
+                        .copied()
+                        .filter(|&i| self.items[i].intersects(ray)),
+                ),
+                Node::Branch(left, right) => {
+                    stack.push(*right);
+                    stack.push(*left);
+                }
+            }
+        }
+
+        result.sort_unstable();
+        result
+    }
+}
@@ -1,22 +1,140 @@
-use std::arch::x86_64;
+mod projection;
+mod sampling;
+pub use projection::Projection;
+pub use sampling::{Filter, SampleStrategy};
+
+use sampling::sample_disk;
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
 
 use crate::{
-    canvas::Canvas, matrix::helpers::Mat4, ray::Ray, tuples::helpers::point, world::World,
+    canvas::Canvas,
+    matrix::helpers::Mat4,
+    ray::Ray,
+    rng::Rng,
+    transformation::Transform,
+    tuples::{
+        helpers::{colors, point, vector},
+        Tuple,
+    },
+    world::World,
 };
 
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self")
+)]
 pub struct Camera {
     hsize: usize,
     vsize: usize,
     fov: f64,
-    transform: Mat4,
+    transform: Transform,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pixel_size: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
     half_width: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
     half_height: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    threads: usize,
+    samples: usize,
+    strategy: SampleStrategy,
+    filter: Filter,
+    seed: u64,
+    adaptive: Option<Adaptive>,
+    aperture: f64,
+    focal_distance: f64,
+    projection: Projection,
+    shutter: (f64, f64),
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Camera {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Camera::serialize(self, serializer)
+    }
+}
+
+/// The pixel size is recomputed from the resolution, and the thread count
+/// is that of the machine loading the camera.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Camera {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut camera = Camera::deserialize(deserializer)?;
+        camera.threads = default_threads();
+        camera.set_size(camera.hsize, camera.vsize);
+        Ok(camera)
+    }
+}
+
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Settings for adaptive anti-aliasing: after a first pass, pixels whose
+/// color differs from a neighbour by more than `threshold` (in any channel)
+/// are split into quadrants, recursively up to `max_depth` levels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Adaptive {
+    pub threshold: f64,
+    pub max_depth: usize,
+}
+
+/// Counts of the camera rays traced by a render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    pub primary_rays: usize,
+    /// Rays spent refining pixels in adaptive mode.
+    pub extra_rays: usize,
+}
+
+impl RenderStats {
+    pub fn total_rays(&self) -> usize {
+        self.primary_rays + self.extra_rays
+    }
+}
+
+fn contrast(a: Tuple, b: Tuple) -> f64 {
+    (a.r() - b.r())
+        .abs()
+        .max((a.g() - b.g()).abs())
+        .max((a.b() - b.b()).abs())
 }
 
 impl Camera {
     pub fn new(hsize: usize, vsize: usize, fov: f64) -> Camera {
-        let half_view = (fov / 2.0).tan();
+        let mut camera = Camera {
+            hsize,
+            vsize,
+            fov,
+            transform: Transform::identity(),
+            half_height: 0.0,
+            half_width: 0.0,
+            pixel_size: 0.0,
+            threads: default_threads(),
+            samples: 1,
+            strategy: SampleStrategy::default(),
+            filter: Filter::default(),
+            seed: 0,
+            adaptive: None,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            projection: Projection::default(),
+            shutter: (0.0, 0.0),
+        };
+        camera.set_size(hsize, vsize);
+        camera
+    }
+
+    /// Changes the resolution, keeping the field of view across the wider
+    /// side of the canvas.
+    pub fn set_size(&mut self, hsize: usize, vsize: usize) {
+        let half_view = (self.fov / 2.0).tan();
         let aspect = hsize as f64 / vsize as f64;
 
         let (half_width, half_height) = if aspect >= 1.0 {
@@ -25,49 +143,252 @@ impl Camera {
             (half_view * aspect, half_view)
         };
 
-        Camera {
-            hsize,
-            vsize,
-            fov,
-            transform: Mat4::identity(),
-            half_height,
-            half_width,
-            pixel_size: (half_width * 2.0) / hsize as f64,
-        }
+        self.hsize = hsize;
+        self.vsize = vsize;
+        self.half_width = half_width;
+        self.half_height = half_height;
+        self.pixel_size = (half_width * 2.0) / hsize as f64;
     }
     pub fn new_transformed(hsize: usize, vsize: usize, fov: f64, transform: Mat4) -> Camera {
         let mut c = Camera::new(hsize, vsize, fov);
-        c.transform = transform;
+        c.set_transform(transform);
         c
     }
 
     pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
-        let xoffset = (px as f64 + 0.5) * self.pixel_size;
-        let yoffset = (py as f64 + 0.5) * self.pixel_size;
+        self.ray_for_sample(px as f64 + 0.5, py as f64 + 0.5)
+    }
 
-        let world_x = self.half_width - xoffset;
-        let world_y = self.half_height - yoffset;
+    /// The ray through a point on the canvas, given in pixels from its top
+    /// left corner, so `(0.5, 0.5)` is the center of the first pixel.
+    ///
+    /// With a non-zero `aperture()` the origin is picked on the lens, and
+    /// with an open `shutter()` the ray is sent at a time within it, both
+    /// seeded by `seed()` and the canvas point.
+    pub fn ray_for_sample(&self, x: f64, y: f64) -> Ray {
+        let (open, close) = self.shutter;
+        if self.aperture <= 0.0 && open == close {
+            return self.ray_through_lens(x, y, 0.0, 0.0).with_time(open);
+        }
+
+        let mut rng = Rng::from_seeds(&[self.seed, x.to_bits(), y.to_bits()]);
+        let (lx, ly) = if self.aperture > 0.0 {
+            sample_disk(rng.next_f64(), rng.next_f64())
+        } else {
+            (0.0, 0.0)
+        };
+        let time = open + (close - open) * rng.next_f64();
+        self.ray_through_lens(x, y, lx, ly).with_time(time)
+    }
 
-        let inv = self.transform().inverse();
+    /// The ray through a point on the canvas starting from a point on the
+    /// lens, given as `lx`/`ly` on the unit disk. All rays through the same
+    /// canvas point meet on the focal plane. The panoramic projections have
+    /// no lens and ignore `lx`/`ly`.
+    pub fn ray_through_lens(&self, x: f64, y: f64, lx: f64, ly: f64) -> Ray {
+        let (origin, direction) = match self.projection {
+            Projection::Perspective => {
+                let world_x = self.half_width - x * self.pixel_size;
+                let world_y = self.half_height - y * self.pixel_size;
+                (point(0, 0, 0), vector(world_x, world_y, -1))
+            }
+            Projection::Orthographic { width } => {
+                let pixel_size = width / self.hsize as f64;
+                let world_x = width / 2.0 - x * pixel_size;
+                let world_y = self.vsize as f64 * pixel_size / 2.0 - y * pixel_size;
+                (point(world_x, world_y, 0), vector(0, 0, -1))
+            }
+            Projection::Equirectangular => {
+                let direction =
+                    projection::equirectangular(x / self.hsize as f64, y / self.vsize as f64);
+                return self.to_world(point(0, 0, 0), direction);
+            }
+            Projection::Fisheye { fov } => {
+                let radius = self.hsize.min(self.vsize) as f64 / 2.0;
+                let dx = (self.hsize as f64 / 2.0 - x) / radius;
+                let dy = (self.vsize as f64 / 2.0 - y) / radius;
+                return self.to_world(point(0, 0, 0), projection::fisheye(fov, dx, dy));
+            }
+        };
 
-        let pixel = inv.clone() * point(world_x, world_y, -1);
-        let origin = inv * point(0, 0, 0);
-        let direction = (pixel - origin).normalized();
+        let radius = self.aperture / 2.0;
+        let focus = origin + direction * self.focal_distance;
+        let lens = origin + vector(lx * radius, ly * radius, 0);
+        self.to_world(lens, focus - lens)
+    }
 
-        Ray::new(origin, direction)
+    fn to_world(&self, origin: Tuple, direction: Tuple) -> Ray {
+        let inv = self.transform.inverse();
+        Ray::new(inv * origin, (inv * direction).normalized())
     }
+
+    /// Renders `world` using `threads()` worker threads.
+    ///
+    /// Rows are handed out one at a time, so the result is identical to
+    /// rendering with a single thread.
     pub fn render(&self, world: &World) -> Canvas {
+        self.render_with_stats(world).0
+    }
+
+    /// Like `render`, also reporting how many camera rays were traced.
+    pub fn render_with_stats(&self, world: &World) -> (Canvas, RenderStats) {
         let mut image = Canvas::new(self.hsize, self.vsize);
+        let mut stats = RenderStats::default();
 
-        for y in 0..self.vsize {
-            for x in 0..self.hsize {
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(&ray);
+        for (y, row) in self
+            .map_rows(|y| self.render_row(world, y))
+            .into_iter()
+            .enumerate()
+        {
+            for (x, (color, rays)) in row.into_iter().enumerate() {
                 image[(x, y)] = color;
+                stats.primary_rays += rays;
+            }
+        }
+
+        if let Some(adaptive) = self.adaptive {
+            let refined = self.map_rows(|y| {
+                (0..self.hsize)
+                    .filter(|&x| self.has_contrast(&image, x, y, adaptive.threshold))
+                    .map(|x| (x, self.refine(world, &adaptive, x as f64, y as f64, 1.0, 1)))
+                    .collect::<Vec<_>>()
+            });
+            for (y, row) in refined.into_iter().enumerate() {
+                for (x, (color, rays)) in row {
+                    image[(x, y)] = color;
+                    stats.extra_rays += rays;
+                }
+            }
+        }
+
+        (image, stats)
+    }
+
+    /// Runs `f` for every row on `threads()` threads, returning the results
+    /// in row order.
+    fn map_rows<T: Send>(&self, f: impl Fn(usize) -> T + Sync) -> Vec<T> {
+        if self.threads <= 1 {
+            return (0..self.vsize).map(f).collect();
+        }
+
+        let next_row = AtomicUsize::new(0);
+        let mut rows = thread::scope(|s| {
+            let workers: Vec<_> = (0..self.threads.min(self.vsize))
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut rows = Vec::new();
+                        loop {
+                            let y = next_row.fetch_add(1, Ordering::Relaxed);
+                            if y >= self.vsize {
+                                break rows;
+                            }
+                            rows.push((y, f(y)));
+                        }
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|w| w.join().expect("Render thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        rows.sort_by_key(|(y, _)| *y);
+        rows.into_iter().map(|(_, row)| row).collect()
+    }
+
+    /// The color of a pixel, averaged over `samples()` rays weighted by
+    /// `filter()`. With a single sample this is the color at its center.
+    pub fn color_for_pixel(&self, world: &World, px: usize, py: usize) -> Tuple {
+        self.sample_pixel(world, px, py).0
+    }
+
+    /// The color of a pixel and the number of rays it took.
+    fn sample_pixel(&self, world: &World, px: usize, py: usize) -> (Tuple, usize) {
+        if self.samples <= 1 && self.strategy == SampleStrategy::Regular {
+            return (world.color_at(&self.ray_for_pixel(px, py)), 1);
+        }
+
+        let mut rng = Rng::from_seeds(&[self.seed, px as u64, py as u64]);
+        let radius = self.filter.radius();
+        let mut sum = colors::black();
+        let mut total_weight = 0.0;
+        let mut rays = 0;
+        for (u, v) in self.strategy.offsets(self.samples, &mut rng) {
+            let (dx, dy) = ((u - 0.5) * 2.0 * radius, (v - 0.5) * 2.0 * radius);
+            let weight = self.filter.weight(dx, dy);
+            if weight <= 0.0 {
+                continue;
             }
+            let ray = self.ray_for_sample(px as f64 + 0.5 + dx, py as f64 + 0.5 + dy);
+            sum = sum + world.color_at(&ray) * weight;
+            total_weight += weight;
+            rays += 1;
+        }
+
+        if total_weight > 0.0 {
+            (sum / total_weight, rays)
+        } else {
+            (world.color_at(&self.ray_for_pixel(px, py)), rays + 1)
         }
+    }
+
+    fn render_row(&self, world: &World, y: usize) -> Vec<(Tuple, usize)> {
+        (0..self.hsize)
+            .map(|x| self.sample_pixel(world, x, y))
+            .collect()
+    }
 
-        image
+    /// Whether the pixel differs from one of its direct neighbours by more
+    /// than `threshold` in any channel.
+    fn has_contrast(&self, image: &Canvas, x: usize, y: usize, threshold: f64) -> bool {
+        let c = image[(x, y)];
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        neighbours
+            .into_iter()
+            .filter(|&(nx, ny)| nx < self.hsize && ny < self.vsize)
+            .any(|n| contrast(c, image[n]) > threshold)
+    }
+
+    /// Averages the four quadrants of the square at (`x`, `y`) with edge
+    /// length `size` (in pixels), subdividing quadrants that still differ
+    /// from that average until `max_depth` is reached.
+    fn refine(
+        &self,
+        world: &World,
+        adaptive: &Adaptive,
+        x: f64,
+        y: f64,
+        size: f64,
+        depth: usize,
+    ) -> (Tuple, usize) {
+        let half = size / 2.0;
+        let quadrants = [(x, y), (x + half, y), (x, y + half), (x + half, y + half)];
+        let mut colors = quadrants
+            .map(|(qx, qy)| world.color_at(&self.ray_for_sample(qx + half / 2.0, qy + half / 2.0)));
+        let mut rays = 4;
+
+        if depth < adaptive.max_depth {
+            let average = colors.iter().fold(colors::black(), |acc, &c| acc + c) / 4.0;
+            for (color, (qx, qy)) in colors.iter_mut().zip(quadrants) {
+                if contrast(*color, average) > adaptive.threshold {
+                    let (refined, extra) = self.refine(world, adaptive, qx, qy, half, depth + 1);
+                    *color = refined;
+                    rays += extra;
+                }
+            }
+        }
+
+        (
+            colors.iter().fold(colors::black(), |acc, &c| acc + c) / 4.0,
+            rays,
+        )
     }
 
     pub fn hsize(&self) -> usize {
@@ -83,25 +404,104 @@ impl Camera {
         self.pixel_size
     }
     pub fn transform(&self) -> &Mat4 {
-        &self.transform
+        self.transform.matrix()
+    }
+    pub fn set_transform(&mut self, transform: Mat4) {
+        self.transform = Transform::new(transform);
+    }
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+    /// Sets the number of threads used by `render`; `1` renders serially.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+    /// Sets the number of rays traced per pixel.
+    pub fn set_samples(&mut self, samples: usize) {
+        self.samples = samples.max(1);
+    }
+    pub fn sample_strategy(&self) -> SampleStrategy {
+        self.strategy
+    }
+    pub fn set_sample_strategy(&mut self, strategy: SampleStrategy) {
+        self.strategy = strategy;
+    }
+    pub fn filter(&self) -> Filter {
+        self.filter
+    }
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    /// Seeds the random sample positions; renders with the same seed are
+    /// identical, regardless of the number of threads.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+    pub fn adaptive(&self) -> Option<Adaptive> {
+        self.adaptive
+    }
+    /// Enables adaptive refinement of high contrast pixels after the first
+    /// pass, or disables it with `None`.
+    pub fn set_adaptive(&mut self, adaptive: Option<Adaptive>) {
+        self.adaptive = adaptive;
+    }
+    pub fn aperture(&self) -> f64 {
+        self.aperture
+    }
+    /// Sets the diameter of the lens; `0.0` is a pinhole camera with
+    /// everything in focus. Blur needs several `samples()` per pixel.
+    pub fn set_aperture(&mut self, aperture: f64) {
+        self.aperture = aperture.max(0.0);
+    }
+    pub fn focal_distance(&self) -> f64 {
+        self.focal_distance
+    }
+    /// Sets the distance from the camera at which objects are in focus.
+    pub fn set_focal_distance(&mut self, focal_distance: f64) {
+        self.focal_distance = focal_distance;
+    }
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+    pub fn shutter(&self) -> (f64, f64) {
+        self.shutter
+    }
+    /// Sets the times at which the shutter opens and closes. Rays are sent
+    /// at times in between, blurring shapes with a `Shape::motion`; an
+    /// instant shutter (the default `(0.0, 0.0)`) freezes them.
+    pub fn set_shutter(&mut self, open: f64, close: f64) {
+        self.shutter = (open, close);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use crate::{
         canvas::Canvas,
         matrix::helpers::Mat4,
+        patterns::CheckerPattern,
         ray::Ray,
-        transformation::{rotation_y, translation, view_transform, PI},
+        shapes::{Plane, Shape},
+        transformation::{rotation_x, rotation_y, translation, view_transform, Motion, PI},
         tuples::{
-            helpers::{color, point, vector},
-            FEquals,
+            helpers::{color, colors, point, vector},
+            FEquals, Tuple,
         },
         world::World,
     };
 
-    use super::Camera;
+    use super::{Adaptive, Camera, Filter, Projection, SampleStrategy};
 
     #[test]
     fn constructing_a_camera() {
@@ -176,4 +576,359 @@ mod tests {
 
         assert_eq!(image[(5, 5)], color(0.38066, 0.47583, 0.2855));
     }
+
+    #[test]
+    fn parallel_render_matches_serial() {
+        let w = World::default();
+
+        let mut c = Camera::new_transformed(
+            21,
+            13,
+            PI / 2.0,
+            view_transform(point(0, 0, -5), point(0, 0, 0), vector(0, 1, 0)),
+        );
+
+        c.set_threads(1);
+        let serial = c.render(&w);
+        c.set_threads(4);
+        let parallel = c.render(&w);
+
+        for y in 0..13 {
+            for x in 0..21 {
+                let (a, b) = (serial[(x, y)], parallel[(x, y)]);
+                assert_eq!(
+                    (a.r().to_bits(), a.g().to_bits(), a.b().to_bits()),
+                    (b.r().to_bits(), b.g().to_bits(), b.b().to_bits())
+                );
+            }
+        }
+    }
+
+    fn checker_world() -> World {
+        let mut w = World::default();
+        let mut floor = Plane::new();
+        floor.material_mut().color =
+            Arc::new(CheckerPattern::new(colors::white(), colors::black()));
+        floor.material_mut().ambient = 1.0;
+        floor.material_mut().diffuse = 0.0;
+        floor.material_mut().specular = 0.0;
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        w.objetcs_mut().clear();
+        w.objetcs_mut().push(Box::new(floor));
+        w
+    }
+
+    fn checker_camera() -> Camera {
+        Camera::new_transformed(
+            16,
+            8,
+            PI / 3.0,
+            view_transform(point(0.3, 1, -3), point(0.7, -1, 3), vector(0, 1, 0)),
+        )
+    }
+
+    #[test]
+    fn single_sample_is_pixel_center() {
+        let w = World::default();
+        let mut c = checker_camera();
+        c.set_samples(1);
+        c.set_filter(Filter::Gaussian);
+        assert_eq!(
+            c.color_for_pixel(&w, 3, 4),
+            w.color_at(&c.ray_for_pixel(3, 4))
+        );
+        assert_eq!(c.ray_for_pixel(3, 4), c.ray_for_sample(3.5, 4.5));
+    }
+
+    #[test]
+    fn supersampling_blends_edges() {
+        let w = checker_world();
+        let mut c = checker_camera();
+        c.set_threads(1);
+        let aliased = c.render(&w);
+        c.set_samples(16);
+        let smooth = c.render(&w);
+
+        let is_grey = |t: Tuple| t.r() > 0.01 && t.r() < 0.99;
+        let count = |canvas: &Canvas| {
+            (0..8)
+                .flat_map(|y| (0..16).map(move |x| (x, y)))
+                .filter(|&p| is_grey(canvas[p]))
+                .count()
+        };
+        assert_eq!(count(&aliased), 0);
+        assert!(count(&smooth) > 0);
+    }
+
+    #[test]
+    fn sampling_is_deterministic() {
+        let w = checker_world();
+        for strategy in [SampleStrategy::Random, SampleStrategy::Stratified] {
+            for filter in [Filter::Box, Filter::Tent, Filter::Gaussian] {
+                let mut c = checker_camera();
+                c.set_samples(4);
+                c.set_sample_strategy(strategy);
+                c.set_filter(filter);
+                c.set_seed(9);
+                c.set_threads(1);
+                let a = c.render(&w);
+                c.set_threads(3);
+                let b = c.render(&w);
+                c.set_seed(10);
+                let other = c.render(&w);
+
+                let pixels = |canvas: &Canvas| {
+                    (0..8)
+                        .flat_map(|y| (0..16).map(move |x| (x, y)))
+                        .map(|p| canvas[p])
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(pixels(&a), pixels(&b));
+                assert_ne!(pixels(&a), pixels(&other));
+            }
+        }
+    }
+
+    #[test]
+    fn render_stats_count_rays() {
+        let w = checker_world();
+        let mut c = checker_camera();
+        let (_, stats) = c.render_with_stats(&w);
+        assert_eq!(stats.primary_rays, 16 * 8);
+        assert_eq!(stats.extra_rays, 0);
+
+        c.set_samples(4);
+        let (_, stats) = c.render_with_stats(&w);
+        assert_eq!(stats.total_rays(), 16 * 8 * 4);
+    }
+
+    #[test]
+    fn adaptive_refines_only_edges() {
+        let w = checker_world();
+        let mut c = checker_camera();
+        c.set_threads(1);
+        let (plain, _) = c.render_with_stats(&w);
+
+        c.set_adaptive(Some(Adaptive {
+            threshold: 0.1,
+            max_depth: 3,
+        }));
+        let (adaptive, stats) = c.render_with_stats(&w);
+        assert_eq!(stats.primary_rays, 16 * 8);
+        assert!(stats.extra_rays > 0);
+        // Far cheaper than supersampling every pixel at the same depth.
+        assert!(stats.extra_rays < 16 * 8 * 64);
+
+        let mut changed = 0;
+        for y in 0..8 {
+            for x in 0..16 {
+                if adaptive[(x, y)] != plain[(x, y)] {
+                    changed += 1;
+                    assert!(c.has_contrast(&plain, x, y, 0.1));
+                }
+            }
+        }
+        assert!(changed > 0);
+
+        c.set_threads(4);
+        let (parallel, parallel_stats) = c.render_with_stats(&w);
+        assert_eq!(parallel_stats, stats);
+        for y in 0..8 {
+            for x in 0..16 {
+                assert_eq!(parallel[(x, y)], adaptive[(x, y)]);
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_skips_flat_images() {
+        let mut w = World::new();
+        let mut wall = Plane::new();
+        wall.set_transform(rotation_x(PI / 2.0) * translation(0.0, 5.0, 0.0));
+        w.objetcs_mut().push(Box::new(wall));
+        let mut c = Camera::new(10, 10, PI / 3.0);
+        c.set_adaptive(Some(Adaptive {
+            threshold: 0.01,
+            max_depth: 4,
+        }));
+        let (_, stats) = c.render_with_stats(&w);
+        assert_eq!(stats.extra_rays, 0);
+    }
+
+    #[test]
+    fn lens_rays_meet_on_focal_plane() {
+        let mut c = Camera::new(201, 101, PI / 2.0);
+        c.set_transform(rotation_y(PI / 4.0) * translation(0.0, -2.0, 5.0));
+        c.set_aperture(0.5);
+        c.set_focal_distance(4.0);
+
+        let pinhole = c.ray_through_lens(20.5, 70.5, 0.0, 0.0);
+        let focus = pinhole.at(4.0 / -(c.transform() * pinhole.direction()).z());
+        for (lx, ly) in [(1.0, 0.0), (0.0, -1.0), (-0.6, 0.8)] {
+            let r = c.ray_through_lens(20.5, 70.5, lx, ly);
+            assert_ne!(r.origin(), pinhole.origin());
+            let t = (focus - r.origin()).magnitude();
+            assert_eq!(r.at(t), focus);
+        }
+    }
+
+    #[test]
+    fn lens_sampling_is_deterministic() {
+        let mut c = Camera::new(11, 11, PI / 2.0);
+        assert_eq!(
+            c.ray_for_sample(3.5, 4.5),
+            c.ray_through_lens(3.5, 4.5, 0.0, 0.0)
+        );
+
+        c.set_aperture(0.2);
+        let a = c.ray_for_sample(3.5, 4.5);
+        assert_eq!(a, c.ray_for_sample(3.5, 4.5));
+        assert_ne!(a.origin(), c.ray_for_sample(3.25, 4.5).origin());
+        assert!(a.origin().x().hypot(a.origin().y()) <= 0.1);
+        c.set_seed(1);
+        assert_ne!(a, c.ray_for_sample(3.5, 4.5));
+    }
+
+    #[test]
+    fn wide_aperture_blurs_out_of_focus_areas() {
+        let w = checker_world();
+        let mut c = checker_camera();
+        c.set_samples(16);
+        let grey = |c: &Camera| {
+            let image = c.render(&w);
+            (0..8)
+                .flat_map(|y| (0..16).map(move |x| (x, y)))
+                .filter(|&p| image[p].r() > 0.01 && image[p].r() < 0.99)
+                .count()
+        };
+        let sharp = grey(&c);
+
+        c.set_aperture(0.5);
+        c.set_focal_distance(1.0);
+        assert!(grey(&c) > sharp);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let mut c = Camera::new(200, 100, PI / 2.0);
+        c.set_projection(Projection::Orthographic { width: 4.0 });
+        let r = c.ray_for_pixel(100, 50);
+        assert_eq!(r.origin(), point(-0.01, -0.01, 0));
+        assert_eq!(r.direction(), vector(0, 0, -1));
+        let r = c.ray_for_pixel(0, 0);
+        assert_eq!(r.origin(), point(1.99, 0.99, 0));
+        assert_eq!(r.direction(), vector(0, 0, -1));
+
+        c.set_transform(rotation_y(PI / 2.0) * translation(0.0, 0.0, -5.0));
+        let r = c.ray_for_sample(100.0, 50.0);
+        assert_eq!(r.origin(), point(0, 0, 5));
+        assert_eq!(r.direction(), vector(1, 0, 0));
+    }
+
+    #[test]
+    fn panoramic_rays_start_at_camera() {
+        let mut c = Camera::new(400, 200, PI / 2.0);
+        c.set_transform(translation(0.0, -2.0, 0.0));
+        c.set_projection(Projection::Equirectangular);
+        let r = c.ray_for_sample(200.0, 100.0);
+        assert_eq!(r.origin(), point(0, 2, 0));
+        assert_eq!(r.direction(), vector(0, 0, -1));
+        assert_eq!(c.ray_for_sample(0.0, 100.0).direction(), vector(0, 0, 1));
+        assert_eq!(c.ray_for_sample(300.0, 0.0).direction(), vector(0, 1, 0));
+
+        c.set_projection(Projection::Fisheye { fov: PI });
+        let r = c.ray_for_sample(200.0, 100.0);
+        assert_eq!(r.origin(), point(0, 2, 0));
+        assert_eq!(r.direction(), vector(0, 0, -1));
+        assert_eq!(c.ray_for_sample(200.0, 0.0).direction(), vector(0, 1, 0));
+    }
+
+    #[test]
+    fn projections_agree_on_left_and_right() {
+        let mut c = Camera::new(100, 100, PI / 2.0);
+        let right = c.ray_for_sample(75.0, 50.0).direction().x();
+        assert!(right < 0.0);
+        for projection in [
+            Projection::Orthographic { width: 2.0 },
+            Projection::Equirectangular,
+            Projection::Fisheye { fov: PI },
+        ] {
+            c.set_projection(projection);
+            let r = c.ray_for_sample(75.0, 50.0);
+            assert!(r.origin().x() + r.direction().x() < 0.0);
+        }
+    }
+
+    #[test]
+    fn shutter_spreads_ray_times() {
+        let mut c = Camera::new(11, 11, PI / 2.0);
+        assert_eq!(c.ray_for_pixel(5, 5).time(), 0.0);
+
+        c.set_shutter(1.0, 1.5);
+        let times: Vec<_> = (0..11).map(|x| c.ray_for_pixel(x, 5).time()).collect();
+        assert!(times.iter().all(|t| (1.0..1.5).contains(t)));
+        assert!(times.iter().any(|&t| t != times[0]));
+        assert_eq!(c.ray_for_pixel(3, 5), c.ray_for_pixel(3, 5));
+        assert_eq!(c.ray_for_pixel(5, 5).origin(), point(0, 0, 0));
+    }
+
+    #[test]
+    fn moving_sphere_is_blurred() {
+        let mut w = World::default();
+        w.objetcs_mut().truncate(1);
+        let mut c = Camera::new(16, 16, PI / 6.0);
+        c.set_transform(view_transform(
+            point(0, 0, -5),
+            point(0, 0, 0),
+            vector(0, 1, 0),
+        ));
+        c.set_samples(16);
+        c.set_threads(1);
+        let edge = |c: &Camera, w: &World| {
+            let image = c.render(w);
+            (0..16).filter(|&x| image[(x, 8)].g() > 0.0).count()
+        };
+        let still = edge(&c, &w);
+
+        w.objetcs_mut()[0].set_motion(Some(Motion::linear(
+            Mat4::identity(),
+            translation(1.0, 0.0, 0.0),
+        )));
+        assert_eq!(edge(&c, &w), still);
+        c.set_shutter(0.0, 1.0);
+        assert!(edge(&c, &w) > still);
+    }
+
+    #[test]
+    fn resizing_keeps_field_of_view() {
+        let mut c = Camera::new(100, 100, PI / 2.0);
+        c.set_size(200, 125);
+        let fresh = Camera::new(200, 125, PI / 2.0);
+        assert_eq!((c.hsize(), c.vsize()), (200, 125));
+        assert!(c.pixel_size().eps_eq(fresh.pixel_size()));
+        assert_eq!(c.ray_for_pixel(0, 0), fresh.ray_for_pixel(0, 0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cameras_round_trip_through_json() {
+        let mut c = Camera::new(160, 120, PI / 3.0);
+        c.set_transform(view_transform(
+            point(0, 1, -5),
+            point(0, 0, 0),
+            vector(0, 1, 0),
+        ));
+        c.set_samples(4);
+        c.set_projection(Projection::Orthographic { width: 4.0 });
+        c.set_shutter(0.0, 0.5);
+
+        let json = serde_json::to_string(&c).unwrap();
+        assert!(!json.contains("pixel_size"));
+        let loaded: Camera = serde_json::from_str(&json).unwrap();
+        assert!(loaded.pixel_size().eps_eq(c.pixel_size()));
+        assert_eq!(loaded.transform(), c.transform());
+        assert_eq!(loaded.samples(), 4);
+        assert_eq!(loaded.projection(), c.projection());
+        assert_eq!(loaded.shutter(), (0.0, 0.5));
+    }
 }
@@ -0,0 +1,74 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::tuples::{helpers::vector, Tuple};
+
+/// How canvas points are mapped to camera rays. The camera looks down its
+/// negative z axis, with y up.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Projection {
+    /// A pinhole (or thin lens) camera with the camera's `fov`.
+    #[default]
+    Perspective,
+    /// Parallel rays from a plane `width` units wide.
+    Orthographic { width: f64 },
+    /// A 360° x 180° panorama, longitude along x and latitude along y.
+    Equirectangular,
+    /// An equidistant fisheye covering `fov` radians across the largest
+    /// circle that fits the canvas. Points outside the circle extend the
+    /// mapping past `fov`.
+    Fisheye { fov: f64 },
+}
+
+/// The direction at the fraction `u`/`v` of an equirectangular canvas.
+pub(crate) fn equirectangular(u: f64, v: f64) -> Tuple {
+    let longitude = (u - 0.5) * TAU;
+    let latitude = (0.5 - v) * PI;
+    vector(
+        -longitude.sin() * latitude.cos(),
+        latitude.sin(),
+        -longitude.cos() * latitude.cos(),
+    )
+}
+
+/// The direction at `dx`/`dy` from the center of a fisheye image, in units
+/// of the image circle's radius.
+pub(crate) fn fisheye(fov: f64, dx: f64, dy: f64) -> Tuple {
+    let r = dx.hypot(dy);
+    if r == 0.0 {
+        return vector(0, 0, -1);
+    }
+    let angle = r * fov / 2.0;
+    vector(dx / r * angle.sin(), dy / r * angle.sin(), -angle.cos())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::tuples::helpers::vector;
+
+    use super::{equirectangular, fisheye};
+
+    #[test]
+    fn equirectangular_directions() {
+        assert_eq!(equirectangular(0.5, 0.5), vector(0, 0, -1));
+        assert_eq!(equirectangular(0.75, 0.5), vector(-1, 0, 0));
+        assert_eq!(equirectangular(0.0, 0.5), vector(0, 0, 1));
+        assert_eq!(equirectangular(0.3, 0.0), vector(0, 1, 0));
+        assert_eq!(equirectangular(0.3, 1.0), vector(0, -1, 0));
+    }
+
+    #[test]
+    fn fisheye_directions() {
+        assert_eq!(fisheye(PI, 0.0, 0.0), vector(0, 0, -1));
+        assert_eq!(fisheye(PI, 1.0, 0.0), vector(1, 0, 0));
+        assert_eq!(fisheye(PI, 0.0, -1.0), vector(0, -1, 0));
+        let d = fisheye(PI / 2.0, 1.0, 0.0);
+        assert_eq!(d, vector(2.0_f64.sqrt() / 2.0, 0, -(2.0_f64.sqrt()) / 2.0));
+    }
+}
@@ -0,0 +1,173 @@
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use crate::rng::Rng;
+
+/// Where inside a pixel (or filter footprint) samples are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum SampleStrategy {
+    /// The centers of an `n x n` grid.
+    #[default]
+    Regular,
+    /// Uniformly random positions, independent of each other, so they can
+    /// clump.
+    Random,
+    /// One random position inside each cell of an `n x n` grid, also known
+    /// as jittered sampling.
+    Stratified,
+}
+
+impl SampleStrategy {
+    /// `samples` positions in `0.0..1.0` on both axes. Grid based strategies
+    /// round `samples` up to the next square number.
+    pub(crate) fn offsets(self, samples: usize, rng: &mut Rng) -> Vec<(f64, f64)> {
+        let samples = samples.max(1);
+        let n = (samples as f64).sqrt().ceil() as usize;
+        let grid = |jitter: &mut dyn FnMut() -> (f64, f64)| {
+            let mut offsets = Vec::with_capacity(n * n);
+            for y in 0..n {
+                for x in 0..n {
+                    let (jx, jy) = jitter();
+                    offsets.push(((x as f64 + jx) / n as f64, (y as f64 + jy) / n as f64));
+                }
+            }
+            offsets
+        };
+
+        match self {
+            SampleStrategy::Regular => grid(&mut || (0.5, 0.5)),
+            SampleStrategy::Random => (0..samples)
+                .map(|_| (rng.next_f64(), rng.next_f64()))
+                .collect(),
+            SampleStrategy::Stratified => grid(&mut || (rng.next_f64(), rng.next_f64())),
+        }
+    }
+}
+
+/// The pixel reconstruction filter used to weight samples. Samples are
+/// spread over the filter's footprint, which for all but `Box` reaches into
+/// the neighbouring pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Filter {
+    #[default]
+    Box,
+    Tent,
+    Gaussian,
+}
+
+impl Filter {
+    /// Half the width of the footprint, in pixels.
+    pub fn radius(self) -> f64 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Gaussian => 1.5,
+        }
+    }
+
+    /// The weight of a sample `dx`/`dy` pixels away from the pixel center.
+    pub fn weight(self, dx: f64, dy: f64) -> f64 {
+        let r = self.radius();
+        match self {
+            Filter::Box => 1.0,
+            Filter::Tent => (1.0 - dx.abs() / r).max(0.0) * (1.0 - dy.abs() / r).max(0.0),
+            Filter::Gaussian => {
+                // sigma = 0.5, shifted so the weight is 0 at the footprint edge
+                let g = |d: f64| ((-2.0 * d * d).exp() - (-2.0 * r * r).exp()).max(0.0);
+                g(dx) * g(dy)
+            }
+        }
+    }
+}
+
+/// Maps `u`/`v` in `0.0..1.0` onto the unit disk, keeping evenly spread
+/// samples evenly spread (Shirley's concentric mapping).
+pub(crate) fn sample_disk(u: f64, v: f64) -> (f64, f64) {
+    let (a, b) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let (r, theta) = if a.abs() > b.abs() {
+        (a, FRAC_PI_4 * (b / a))
+    } else {
+        (b, FRAC_PI_2 - FRAC_PI_4 * (a / b))
+    };
+    (r * theta.cos(), r * theta.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rng::Rng, tuples::FEquals};
+
+    use super::{sample_disk, Filter, SampleStrategy};
+
+    #[test]
+    fn regular_grid() {
+        let mut rng = Rng::new(0);
+        let offsets = SampleStrategy::Regular.offsets(4, &mut rng);
+        assert_eq!(
+            offsets,
+            [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+        );
+        assert_eq!(SampleStrategy::Regular.offsets(1, &mut rng), [(0.5, 0.5)]);
+        assert_eq!(SampleStrategy::Regular.offsets(5, &mut rng).len(), 9);
+    }
+
+    #[test]
+    fn random_is_reproducible() {
+        let a = SampleStrategy::Random.offsets(5, &mut Rng::new(1));
+        let b = SampleStrategy::Random.offsets(5, &mut Rng::new(1));
+        let c = SampleStrategy::Random.offsets(5, &mut Rng::new(2));
+        assert_eq!(a.len(), 5);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a
+            .iter()
+            .all(|(x, y)| (0.0..1.0).contains(x) && (0.0..1.0).contains(y)));
+    }
+
+    #[test]
+    fn stratified_one_per_cell() {
+        let offsets = SampleStrategy::Stratified.offsets(9, &mut Rng::new(3));
+        assert_eq!(offsets.len(), 9);
+        for (i, (x, y)) in offsets.iter().enumerate() {
+            let (cx, cy) = ((i % 3) as f64 / 3.0, (i / 3) as f64 / 3.0);
+            assert!((cx..cx + 1.0 / 3.0).contains(x));
+            assert!((cy..cy + 1.0 / 3.0).contains(y));
+        }
+    }
+
+    #[test]
+    fn filter_weights() {
+        assert!(Filter::Box.weight(0.4, -0.4).eps_eq(1.0));
+        assert!(Filter::Tent.weight(0.0, 0.0).eps_eq(1.0));
+        assert!(Filter::Tent.weight(0.5, 0.0).eps_eq(0.5));
+        assert!(Filter::Tent.weight(1.0, 0.0).eps_eq(0.0));
+        assert!(Filter::Gaussian.weight(0.0, 0.0) > Filter::Gaussian.weight(0.5, 0.0));
+        assert!(Filter::Gaussian.weight(1.5, 0.0).eps_eq(0.0));
+    }
+
+    #[test]
+    fn disk_samples_stay_on_unit_disk() {
+        assert_eq!(sample_disk(0.5, 0.5), (0.0, 0.0));
+        let (x, y) = sample_disk(1.0, 0.5);
+        assert!(x.eps_eq(1.0) && y.eps_eq(0.0));
+        let (x, y) = sample_disk(0.5, 0.0);
+        assert!(x.eps_eq(0.0) && y.eps_eq(-1.0));
+
+        let mut rng = Rng::new(3);
+        for _ in 0..100 {
+            let (x, y) = sample_disk(rng.next_f64(), rng.next_f64());
+            assert!(x * x + y * y <= 1.0 + 1e-9);
+        }
+    }
+}
@@ -1,6 +1,9 @@
+mod ppm;
+pub use ppm::{PpmError, PpmFormat};
+
 use std::ops::{Index, IndexMut};
 
-use image::{DynamicImage, GenericImage, ImageResult, Rgba};
+use image::{DynamicImage, GenericImage, ImageFormat, ImageResult, Rgba};
 
 use crate::tuples::{helpers::colors, Tuple};
 
@@ -22,26 +25,46 @@ impl Canvas {
     }
 
     pub fn save(&self, path: &str) -> ImageResult<()> {
-        let mut img = DynamicImage::new_rgba8(self.width as u32, self.height as u32);
+        self.to_image().save(path)
+    }
+
+    /// Saves the canvas as `format`, whatever the extension of `path`.
+    pub fn save_with_format(&self, path: &str, format: ImageFormat) -> ImageResult<()> {
+        self.to_image().save_with_format(path, format)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn to_image(&self) -> DynamicImage {
+        let mut img = DynamicImage::new_rgb8(self.width as u32, self.height as u32);
 
         let a = 255;
 
-        (0..self.width).into_iter().for_each(|x| {
-            (0..self.height).into_iter().for_each(|y| {
+        (0..self.width).for_each(|x| {
+            (0..self.height).for_each(|y| {
                 let pixel = &self[(x, y)];
 
-                let r = (pixel.r() * 255.0) as u8;
-                let g = (pixel.g() * 255.0) as u8;
-                let b = (pixel.b() * 255.0) as u8;
+                let [r, g, b] = [pixel.r(), pixel.g(), pixel.b()].map(to_sample);
 
                 img.put_pixel(x as u32, y as u32, Rgba([r, g, b, a]));
             })
         });
 
-        img.save(path)
+        img
     }
 }
 
+/// A color channel as an 8 bit sample, clamped to `0.0..=1.0`. Shared by
+/// the `image` and PPM output so both write the same values.
+fn to_sample(c: f64) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
 impl Index<(usize, usize)> for Canvas {
     type Output = Tuple;
 
@@ -94,4 +117,19 @@ mod tests {
 
         c.save("./temp/test.png").expect("Saving image failed");
     }
+
+    #[test]
+    fn image_samples_match_ppm() {
+        let mut c = Canvas::new(3, 1);
+        c[(0, 0)] = color(1.5, 0.5, -0.5);
+        c[(1, 0)] = color(0.999, 0.002, 0.2);
+        c[(2, 0)] = color(1, 1, 1);
+
+        let img = c.to_image().to_rgb8();
+        let ppm = c.to_ppm_binary();
+        let pixels = &ppm[ppm.len() - 9..];
+        assert_eq!(img.as_raw().as_slice(), pixels);
+        assert_eq!(img.get_pixel(0, 0).0, [255, 128, 0]);
+        assert_eq!(img.get_pixel(1, 0).0, [255, 1, 51]);
+    }
 }
@@ -0,0 +1,359 @@
+//! Reading and writing the PPM image format, plain (P3) and binary (P6).
+
+use std::{error::Error, fmt::Display, fs, io};
+
+use crate::tuples::helpers::color;
+
+use super::{to_sample, Canvas};
+
+/// The longest line a plain PPM file may contain.
+const MAX_LINE: usize = 70;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PpmFormat {
+    /// P3: samples as decimal text.
+    Plain,
+    /// P6: samples as raw bytes.
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum PpmError {
+    Io(io::Error),
+    /// The data is not a PPM image this reader understands.
+    Invalid(String),
+}
+
+impl Display for PpmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PpmError::Io(e) => write!(f, "Failed to read PPM file: {e}"),
+            PpmError::Invalid(message) => write!(f, "Invalid PPM: {message}"),
+        }
+    }
+}
+
+impl Error for PpmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PpmError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PpmError {
+    fn from(value: io::Error) -> Self {
+        PpmError::Io(value)
+    }
+}
+
+fn invalid<T>(message: impl Into<String>) -> Result<T, PpmError> {
+    Err(PpmError::Invalid(message.into()))
+}
+
+impl Canvas {
+    /// The canvas as a plain (P3) PPM file. Every row starts on a new line
+    /// and no line is longer than 70 characters.
+    pub fn to_ppm(&self) -> String {
+        let mut ppm = format!("P3\n{} {}\n255\n", self.width, self.height);
+        let mut line = String::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self[(x, y)];
+                for c in [pixel.r(), pixel.g(), pixel.b()] {
+                    let sample = to_sample(c).to_string();
+                    if !line.is_empty() && line.len() + 1 + sample.len() > MAX_LINE {
+                        ppm.push_str(&line);
+                        ppm.push('\n');
+                        line.clear();
+                    }
+                    if !line.is_empty() {
+                        line.push(' ');
+                    }
+                    line.push_str(&sample);
+                }
+            }
+            ppm.push_str(&line);
+            ppm.push('\n');
+            line.clear();
+        }
+
+        ppm
+    }
+
+    /// The canvas as a binary (P6) PPM file.
+    pub fn to_ppm_binary(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.reserve(self.width * self.height * 3);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self[(x, y)];
+                ppm.extend([pixel.r(), pixel.g(), pixel.b()].map(to_sample));
+            }
+        }
+
+        ppm
+    }
+
+    pub fn save_ppm(&self, path: &str, format: PpmFormat) -> io::Result<()> {
+        match format {
+            PpmFormat::Plain => fs::write(path, self.to_ppm()),
+            PpmFormat::Binary => fs::write(path, self.to_ppm_binary()),
+        }
+    }
+
+    /// Reads a P3 or P6 file, with any maximum sample value up to 65535.
+    pub fn from_ppm(data: &[u8]) -> Result<Canvas, PpmError> {
+        let mut reader = Reader { data, pos: 0 };
+
+        let binary = match reader.token()? {
+            b"P3" => false,
+            b"P6" => true,
+            magic => {
+                return invalid(format!(
+                    "Unsupported magic number `{}`",
+                    String::from_utf8_lossy(magic)
+                ))
+            }
+        };
+        let width = reader.number("width")?;
+        let height = reader.number("height")?;
+        let max = reader.number("maximum value")?;
+        if max == 0 || max > 65535 {
+            return invalid(format!("Maximum value {max} is out of range"));
+        }
+
+        // Every sample takes at least a byte, so the data bounds the size.
+        let samples = width.checked_mul(height).and_then(|n| n.checked_mul(3));
+        if samples.is_none_or(|n| n > data.len()) {
+            return invalid(format!("{width}x{height} pixels don't fit the data"));
+        }
+
+        if binary {
+            // Exactly one whitespace character separates the header from the
+            // samples.
+            reader.pos += 1;
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        let max_f = max as f64;
+        for y in 0..height {
+            for x in 0..width {
+                let mut rgb = [0.0; 3];
+                for c in &mut rgb {
+                    let sample = if binary {
+                        reader.binary_sample(max > 255)?
+                    } else {
+                        reader.number("sample")?
+                    };
+                    if sample > max {
+                        return invalid(format!("Sample {sample} is above the maximum {max}"));
+                    }
+                    *c = sample as f64 / max_f;
+                }
+                canvas[(x, y)] = color(rgb[0], rgb[1], rgb[2]);
+            }
+        }
+
+        Ok(canvas)
+    }
+
+    pub fn load_ppm(path: &str) -> Result<Canvas, PpmError> {
+        Canvas::from_ppm(&fs::read(path)?)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// The next whitespace separated token, skipping `#` comments.
+    fn token(&mut self) -> Result<&'a [u8], PpmError> {
+        loop {
+            match self.data.get(self.pos) {
+                Some(b'#') => {
+                    while !matches!(self.data.get(self.pos), None | Some(b'\n' | b'\r')) {
+                        self.pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                Some(_) => break,
+                None => return invalid("Unexpected end of file"),
+            }
+        }
+
+        let start = self.pos;
+        while self
+            .data
+            .get(self.pos)
+            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+        {
+            self.pos += 1;
+        }
+        Ok(&self.data[start..self.pos])
+    }
+
+    fn number(&mut self, what: &str) -> Result<usize, PpmError> {
+        let token = self.token()?;
+        match std::str::from_utf8(token).ok().and_then(|s| s.parse().ok()) {
+            Some(n) => Ok(n),
+            None => invalid(format!(
+                "Expected the {what}, found `{}`",
+                String::from_utf8_lossy(token)
+            )),
+        }
+    }
+
+    fn binary_sample(&mut self, wide: bool) -> Result<usize, PpmError> {
+        let len = if wide { 2 } else { 1 };
+        let Some(bytes) = self.data.get(self.pos..self.pos + len) else {
+            return invalid("Unexpected end of file");
+        };
+        self.pos += len;
+        Ok(bytes.iter().fold(0, |n, b| n << 8 | *b as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tuples::helpers::color;
+
+    use super::{Canvas, PpmError, PpmFormat};
+
+    #[test]
+    fn constructing_the_ppm_header() {
+        let c = Canvas::new(5, 3);
+        let ppm = c.to_ppm();
+        assert!(ppm.starts_with("P3\n5 3\n255\n"));
+    }
+
+    #[test]
+    fn constructing_the_ppm_pixel_data() {
+        let mut c = Canvas::new(5, 3);
+        c[(0, 0)] = color(1.5, 0, 0);
+        c[(2, 1)] = color(0, 0.5, 0);
+        c[(4, 2)] = color(-0.5, 0, 1);
+
+        let ppm = c.to_ppm();
+        let lines: Vec<_> = ppm.lines().skip(3).collect();
+        assert_eq!(
+            lines,
+            [
+                "255 0 0 0 0 0 0 0 0 0 0 0 0 0 0",
+                "0 0 0 0 0 0 0 128 0 0 0 0 0 0 0",
+                "0 0 0 0 0 0 0 0 0 0 0 0 0 0 255",
+            ]
+        );
+    }
+
+    #[test]
+    fn splitting_long_lines() {
+        let mut c = Canvas::new(10, 2);
+        for x in 0..10 {
+            for y in 0..2 {
+                c[(x, y)] = color(1, 0.8, 0.6);
+            }
+        }
+
+        let ppm = c.to_ppm();
+        let lines: Vec<_> = ppm.lines().skip(3).collect();
+        assert_eq!(
+            lines,
+            [
+                "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+                "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+            ]
+        );
+        assert!(ppm.lines().all(|l| l.len() <= 70));
+    }
+
+    #[test]
+    fn ppm_files_end_with_a_newline() {
+        let c = Canvas::new(5, 3);
+        assert!(c.to_ppm().ends_with('\n'));
+    }
+
+    #[test]
+    fn binary_ppm() {
+        let mut c = Canvas::new(2, 1);
+        c[(0, 0)] = color(1, 0.5, 0);
+        c[(1, 0)] = color(0, 0, 2);
+
+        let ppm = c.to_ppm_binary();
+        assert_eq!(&ppm[..11], b"P6\n2 1\n255\n");
+        assert_eq!(&ppm[11..], [255, 128, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn round_trips_are_lossless_at_8_bits() {
+        let mut c = Canvas::new(16, 16);
+        for x in 0..16 {
+            for y in 0..16 {
+                let v = (x * 16 + y) as f64;
+                c[(x, y)] = color(v / 255.0, (255.0 - v) / 255.0, (v * 7.0 % 256.0) / 255.0);
+            }
+        }
+
+        for ppm in [c.to_ppm().into_bytes(), c.to_ppm_binary()] {
+            let read = Canvas::from_ppm(&ppm).unwrap();
+            assert_eq!((read.width(), read.height()), (16, 16));
+            assert_eq!(read.to_ppm_binary(), c.to_ppm_binary());
+        }
+    }
+
+    #[test]
+    fn reading_comments_and_other_maximum_values() {
+        let ppm = b"P3\n# made by hand\n2 1 # width and height\n100\n100 50 0\n0 0 100\n";
+        let c = Canvas::from_ppm(ppm).unwrap();
+        assert_eq!(c[(0, 0)], color(1, 0.5, 0));
+        assert_eq!(c[(1, 0)], color(0, 0, 1));
+
+        let mut ppm = b"P6 1 1 65535\n".to_vec();
+        ppm.extend([0xff, 0xff, 0x80, 0x00, 0x00, 0x00]);
+        let c = Canvas::from_ppm(&ppm).unwrap();
+        assert_eq!(c[(0, 0)], color(1, 32768.0 / 65535.0, 0));
+    }
+
+    #[test]
+    fn reading_invalid_ppm() {
+        for ppm in [
+            &b"P5\n1 1\n255\n0"[..],
+            b"P3\n1 1\n",
+            b"P3\n1 1\n255\n0 0",
+            b"P3\n1 1\n255\n0 0 256",
+            b"P3\n1 x\n255\n0 0 0",
+            b"P3\n1 1\n0\n0 0 0",
+            b"P6\n2 1\n255\n\x00\x00\x00",
+            b"P6\n100000 100000\n255\n\x00\x00\x00",
+        ] {
+            assert!(matches!(Canvas::from_ppm(ppm), Err(PpmError::Invalid(_))));
+        }
+        assert!(matches!(
+            Canvas::load_ppm("./temp/missing.ppm"),
+            Err(PpmError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn saving_and_loading_ppm() {
+        let mut c = Canvas::new(3, 2);
+        c[(1, 1)] = color(0.2, 0.4, 0.6);
+
+        std::fs::create_dir_all("./temp").unwrap();
+        for (path, format) in [
+            ("./temp/test_plain.ppm", PpmFormat::Plain),
+            ("./temp/test_binary.ppm", PpmFormat::Binary),
+        ] {
+            c.save_ppm(path, format).unwrap();
+            let read = Canvas::load_ppm(path).unwrap();
+            assert_eq!(read[(1, 1)], color(0.2, 0.4, 0.6));
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! A ray tracer following "The Ray Tracer Challenge".
+//!
+//! The most commonly used types are re-exported at the crate root; the
+//! modules stay public for the helper functions (`tuples::helpers`,
+//! `transformation::helper`, ...) that build on them.
+
+pub mod bounds;
+pub(crate) mod bvh;
+pub mod camera;
+pub mod canvas;
+pub mod light;
+pub mod material;
+pub mod matrix;
+pub mod obj;
+pub mod patterns;
+pub mod ray;
+pub(crate) mod rng;
+pub mod scene;
+pub mod shapes;
+pub mod transformation;
+pub mod tuples;
+pub mod world;
+
+pub use bounds::BoundingBox;
+pub use camera::{Adaptive, Camera, Filter, Projection, RenderStats, SampleStrategy};
+pub use canvas::{Canvas, PpmError, PpmFormat};
+pub use light::{AreaLight, Attenuation, DirectionalLight, Light, PointLight, SpotLight};
+pub use material::Material;
+pub use matrix::helpers::Mat4;
+pub use obj::{load_obj, parse_obj, ObjError, ObjFile};
+pub use patterns::{CheckerPattern, GradientPattern, Pattern, RingPattern, Solid, StripePattern};
+pub use ray::{Intersection, Intersections, Ray};
+pub use scene::{load_scene, parse_scene, Scene, SceneError};
+pub use shapes::{
+    Cone, Csg, CsgOperation, Cube, Cylinder, Group, Mesh, Plane, Shape, SmoothTriangle, Sphere,
+    Triangle,
+};
+pub use transformation::{
+    helper::TransformationBuilder, rotation_x, rotation_y, rotation_z, scaling, shearing,
+    translation, view_transform, Motion, PI,
+};
+pub use tuples::Tuple;
+pub use world::World;
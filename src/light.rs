@@ -1,38 +1,156 @@
+mod area;
+pub use area::AreaLight;
+mod directional;
+pub use directional::DirectionalLight;
+mod point;
+pub use point::PointLight;
+mod spot;
+pub use spot::SpotLight;
+
+use std::fmt::Debug;
+
 use crate::tuples::Tuple;
 
-#[derive(Debug, PartialEq)]
-pub struct PointLight {
-    intensity: Tuple,
-    position: Tuple,
+/// Where light arrives from, as seen from a shaded point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightSample {
+    /// Normalized vector from the point towards the light.
+    pub direction: Tuple,
+    /// How far the light is along `direction`; infinite for lights without
+    /// a position, so every object along the way casts a shadow.
+    pub distance: f64,
+}
+
+impl LightSample {
+    /// The sample for a light at `position`.
+    pub fn towards(point: Tuple, position: Tuple) -> LightSample {
+        let v = position - point;
+        LightSample {
+            direction: v.normalized(),
+            distance: v.magnitude(),
+        }
+    }
+}
+
+/// How a light's strength falls off with distance.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Attenuation {
+    /// The same strength at any distance.
+    #[default]
+    None,
+    /// Strength divided by the distance.
+    Linear,
+    /// Strength divided by the squared distance.
+    InverseSquare,
+    /// Strength divided by `constant + linear * d + quadratic * d²`. See
+    /// `Attenuation::coefficients` for a checked constructor.
+    Coefficients {
+        constant: f64,
+        linear: f64,
+        quadratic: f64,
+    },
 }
 
-impl PointLight {
-    pub fn new(intensity: Tuple, position: Tuple) -> PointLight {
-        PointLight {
-            intensity,
-            position,
+impl Attenuation {
+    /// Falloff with the given coefficients, or `None` if any of them is
+    /// negative (or NaN) or all of them are zero.
+    pub fn coefficients(constant: f64, linear: f64, quadratic: f64) -> Option<Attenuation> {
+        let all = [constant, linear, quadratic];
+        let valid = all.iter().all(|c| *c >= 0.0) && all.iter().any(|c| *c > 0.0);
+        if !valid {
+            return None;
         }
+        Some(Attenuation::Coefficients {
+            constant,
+            linear,
+            quadratic,
+        })
     }
-    pub fn intensity(&self) -> &Tuple {
-        &self.intensity
+
+    /// The factor the light's strength is multiplied with at `distance`. It
+    /// never exceeds `1.0`, so the light stays finite close to its position.
+    pub fn factor(&self, distance: f64) -> f64 {
+        let (constant, linear, quadratic) = match *self {
+            Attenuation::None => return 1.0,
+            Attenuation::Linear => (0.0, 1.0, 0.0),
+            Attenuation::InverseSquare => (0.0, 0.0, 1.0),
+            Attenuation::Coefficients {
+                constant,
+                linear,
+                quadratic,
+            } => (constant, linear, quadratic),
+        };
+        1.0 / (constant + linear * distance + quadratic * distance * distance).max(1.0)
     }
-    pub fn position(&self) -> &Tuple {
-        &self.position
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(tag = "type"))]
+pub trait Light: Debug + Send + Sync {
+    /// The color of the light arriving at `point`, ignoring shadows.
+    fn intensity_at(&self, point: Tuple) -> Tuple;
+
+    /// The directions shading and shadows at `point` average over. Lights
+    /// with a single position return one sample.
+    fn samples(&self, point: Tuple) -> Vec<LightSample>;
+
+    fn attenuation(&self) -> Attenuation {
+        Attenuation::None
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tuples::helpers::{color, point};
+    use crate::tuples::FEquals;
+
+    use super::Attenuation;
+
+    #[test]
+    fn attenuation_factors() {
+        let cases = [
+            (Attenuation::None, 4.0, 1.0),
+            (Attenuation::default(), 1000.0, 1.0),
+            (Attenuation::Linear, 4.0, 0.25),
+            (Attenuation::InverseSquare, 4.0, 0.0625),
+            (
+                Attenuation::Coefficients {
+                    constant: 1.0,
+                    linear: 0.5,
+                    quadratic: 0.25,
+                },
+                2.0,
+                1.0 / 3.0,
+            ),
+        ];
+        for (attenuation, distance, expected) in cases {
+            assert!(
+                attenuation.factor(distance).eps_eq(expected),
+                "{attenuation:?}"
+            );
+        }
+    }
 
-    use super::PointLight;
+    #[test]
+    fn attenuation_is_finite_at_the_light() {
+        assert_eq!(Attenuation::Linear.factor(0.0), 1.0);
+        assert_eq!(Attenuation::InverseSquare.factor(0.5), 1.0);
+        let zero = Attenuation::Coefficients {
+            constant: 0.0,
+            linear: 0.0,
+            quadratic: 0.0,
+        };
+        assert_eq!(zero.factor(3.0), 1.0);
+    }
 
     #[test]
-    fn light_has_position_and_intensity() {
-        let intensity = color(1, 1, 1);
-        let position = point(0, 0, 0);
-        let light = PointLight::new(intensity, position);
-        assert_eq!(light.position, position);
-        assert_eq!(light.intensity, intensity);
+    fn checked_coefficients() {
+        assert!(Attenuation::coefficients(1.0, 0.0, 0.5).is_some());
+        assert!(Attenuation::coefficients(0.0, 0.0, 0.0).is_none());
+        assert!(Attenuation::coefficients(1.0, -0.5, 0.0).is_none());
+        assert!(Attenuation::coefficients(f64::NAN, 1.0, 0.0).is_none());
     }
 }
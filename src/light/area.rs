@@ -0,0 +1,202 @@
+use crate::{rng::Rng, tuples::Tuple};
+
+use super::{Attenuation, Light, LightSample};
+
+/// A rectangular light spanned by `full_uvec` and `full_vvec` from `corner`,
+/// sampled as a grid of `usteps` by `vsteps` cells for soft shadows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AreaLight {
+    intensity: Tuple,
+    corner: Tuple,
+    uvec: Tuple,
+    usteps: usize,
+    vvec: Tuple,
+    vsteps: usize,
+    jitter: Option<u64>,
+    attenuation: Attenuation,
+}
+
+impl AreaLight {
+    pub fn new(
+        intensity: Tuple,
+        corner: Tuple,
+        full_uvec: Tuple,
+        usteps: usize,
+        full_vvec: Tuple,
+        vsteps: usize,
+    ) -> AreaLight {
+        let (usteps, vsteps) = (usteps.max(1), vsteps.max(1));
+        AreaLight {
+            intensity,
+            corner,
+            uvec: full_uvec / usteps as f64,
+            usteps,
+            vvec: full_vvec / vsteps as f64,
+            vsteps,
+            jitter: None,
+            attenuation: Attenuation::None,
+        }
+    }
+
+    /// Samples a random point in each cell instead of its center. The same
+    /// seed always gives the same samples for a given shading point.
+    pub fn jittered(mut self, seed: u64) -> AreaLight {
+        self.jitter = Some(seed);
+        self
+    }
+
+    /// Attenuation is applied per sample, using that sample's distance.
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> AreaLight {
+        self.attenuation = attenuation;
+        self
+    }
+
+    pub fn intensity(&self) -> &Tuple {
+        &self.intensity
+    }
+    pub fn corner(&self) -> Tuple {
+        self.corner
+    }
+    pub fn uvec(&self) -> Tuple {
+        self.uvec
+    }
+    pub fn usteps(&self) -> usize {
+        self.usteps
+    }
+    pub fn vvec(&self) -> Tuple {
+        self.vvec
+    }
+    pub fn vsteps(&self) -> usize {
+        self.vsteps
+    }
+    pub fn jitter(&self) -> Option<u64> {
+        self.jitter
+    }
+
+    /// The center of the light.
+    pub fn position(&self) -> Tuple {
+        self.corner
+            + self.uvec * (self.usteps as f64 / 2.0)
+            + self.vvec * (self.vsteps as f64 / 2.0)
+    }
+
+    /// The point in cell (`u`, `v`), offset by `ju`/`jv` in `0.0..1.0`.
+    pub fn point_on_light(&self, u: usize, v: usize, ju: f64, jv: f64) -> Tuple {
+        self.corner + self.uvec * (u as f64 + ju) + self.vvec * (v as f64 + jv)
+    }
+
+    /// One point per cell, as seen from `point`.
+    pub fn sample_points(&self, point: Tuple) -> Vec<Tuple> {
+        let mut rng = self.jitter.map(|seed| {
+            Rng::from_seeds(&[
+                seed,
+                point.x().to_bits(),
+                point.y().to_bits(),
+                point.z().to_bits(),
+            ])
+        });
+
+        let mut samples = Vec::with_capacity(self.usteps * self.vsteps);
+        for v in 0..self.vsteps {
+            for u in 0..self.usteps {
+                let (ju, jv) = match &mut rng {
+                    Some(rng) => (rng.next_f64(), rng.next_f64()),
+                    None => (0.5, 0.5),
+                };
+                samples.push(self.point_on_light(u, v, ju, jv));
+            }
+        }
+        samples
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "area"))]
+impl Light for AreaLight {
+    fn intensity_at(&self, _: Tuple) -> Tuple {
+        self.intensity
+    }
+
+    fn samples(&self, point: Tuple) -> Vec<LightSample> {
+        self.sample_points(point)
+            .into_iter()
+            .map(|p| LightSample::towards(point, p))
+            .collect()
+    }
+
+    fn attenuation(&self) -> Attenuation {
+        self.attenuation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tuples::helpers::{color, point, vector};
+
+    use super::AreaLight;
+
+    #[test]
+    fn creating_area_light() {
+        let light = AreaLight::new(
+            color(1, 1, 1),
+            point(0, 0, 0),
+            vector(2, 0, 0),
+            4,
+            vector(0, 0, 1),
+            2,
+        );
+        assert_eq!(light.corner(), point(0, 0, 0));
+        assert_eq!(light.uvec(), vector(0.5, 0, 0));
+        assert_eq!(light.usteps(), 4);
+        assert_eq!(light.vvec(), vector(0, 0, 0.5));
+        assert_eq!(light.vsteps(), 2);
+        assert_eq!(light.position(), point(1, 0, 0.5));
+    }
+
+    #[test]
+    fn point_on_area_light() {
+        let light = AreaLight::new(
+            color(1, 1, 1),
+            point(0, 0, 0),
+            vector(2, 0, 0),
+            4,
+            vector(0, 0, 1),
+            2,
+        );
+        let cases = [
+            (0, 0, point(0.25, 0, 0.25)),
+            (1, 0, point(0.75, 0, 0.25)),
+            (0, 1, point(0.25, 0, 0.75)),
+            (2, 0, point(1.25, 0, 0.25)),
+            (3, 1, point(1.75, 0, 0.75)),
+        ];
+        for (u, v, expected) in cases {
+            assert_eq!(light.point_on_light(u, v, 0.5, 0.5), expected);
+        }
+        let samples = light.sample_points(point(0, 5, 0));
+        assert_eq!(samples.len(), 8);
+        assert_eq!(samples[7], point(1.75, 0, 0.75));
+    }
+
+    #[test]
+    fn jittered_samples_stay_in_cells() {
+        let light = AreaLight::new(
+            color(1, 1, 1),
+            point(0, 0, 0),
+            vector(2, 0, 0),
+            4,
+            vector(0, 0, 1),
+            2,
+        )
+        .jittered(3);
+        let p = point(0, 5, 0);
+        let samples = light.sample_points(p);
+        assert_eq!(samples, light.sample_points(p));
+        assert_ne!(samples, light.jittered(4).sample_points(p));
+        for (i, s) in samples.iter().enumerate() {
+            let (u, v) = ((i % 4) as f64, (i / 4) as f64);
+            assert!((u * 0.5..(u + 1.0) * 0.5).contains(&s.x()));
+            assert!((v * 0.5..(v + 1.0) * 0.5).contains(&s.z()));
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::tuples::Tuple;
+
+use super::{Light, LightSample};
+
+/// An infinitely distant light, like the sun, shining along `direction`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirectionalLight {
+    intensity: Tuple,
+    direction: Tuple,
+}
+
+impl DirectionalLight {
+    pub fn new(intensity: Tuple, direction: Tuple) -> DirectionalLight {
+        DirectionalLight {
+            intensity,
+            direction: direction.normalized(),
+        }
+    }
+    pub fn intensity(&self) -> &Tuple {
+        &self.intensity
+    }
+    pub fn direction(&self) -> &Tuple {
+        &self.direction
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "directional"))]
+impl Light for DirectionalLight {
+    fn intensity_at(&self, _: Tuple) -> Tuple {
+        self.intensity
+    }
+
+    fn samples(&self, _: Tuple) -> Vec<LightSample> {
+        vec![LightSample {
+            direction: -self.direction,
+            distance: f64::INFINITY,
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        light::Light,
+        tuples::helpers::{color, point, vector},
+    };
+
+    use super::DirectionalLight;
+
+    #[test]
+    fn same_direction_everywhere() {
+        let light = DirectionalLight::new(color(1, 1, 1), vector(0, -2, 0));
+        assert_eq!(light.direction(), &vector(0, -1, 0));
+        for p in [point(0, 0, 0), point(100, -50, 3)] {
+            let samples = light.samples(p);
+            assert_eq!(samples.len(), 1);
+            assert_eq!(samples[0].direction, vector(0, 1, 0));
+            assert_eq!(samples[0].distance, f64::INFINITY);
+            assert_eq!(light.intensity_at(p), color(1, 1, 1));
+        }
+    }
+}
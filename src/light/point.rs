@@ -0,0 +1,78 @@
+use crate::tuples::Tuple;
+
+use super::{Attenuation, Light, LightSample};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointLight {
+    intensity: Tuple,
+    position: Tuple,
+    attenuation: Attenuation,
+}
+
+impl PointLight {
+    pub fn new(intensity: Tuple, position: Tuple) -> PointLight {
+        PointLight {
+            intensity,
+            position,
+            attenuation: Attenuation::None,
+        }
+    }
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> PointLight {
+        self.attenuation = attenuation;
+        self
+    }
+    pub fn intensity(&self) -> &Tuple {
+        &self.intensity
+    }
+    pub fn position(&self) -> &Tuple {
+        &self.position
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "point"))]
+impl Light for PointLight {
+    fn intensity_at(&self, _: Tuple) -> Tuple {
+        self.intensity
+    }
+
+    fn samples(&self, point: Tuple) -> Vec<LightSample> {
+        vec![LightSample::towards(point, self.position)]
+    }
+
+    fn attenuation(&self) -> Attenuation {
+        self.attenuation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        light::Light,
+        tuples::{
+            helpers::{color, point, vector},
+            FEquals,
+        },
+    };
+
+    use super::PointLight;
+
+    #[test]
+    fn light_has_position_and_intensity() {
+        let intensity = color(1, 1, 1);
+        let position = point(0, 0, 0);
+        let light = PointLight::new(intensity, position);
+        assert_eq!(light.position, position);
+        assert_eq!(light.intensity, intensity);
+    }
+
+    #[test]
+    fn point_light_has_one_sample() {
+        let light = PointLight::new(color(1, 1, 1), point(1, 2, 3));
+        let samples = light.samples(point(1, 2, 0));
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].direction, vector(0, 0, 1));
+        assert!(samples[0].distance.eps_eq(3.0));
+        assert_eq!(light.intensity_at(point(5, 5, 5)), color(1, 1, 1));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::tuples::Tuple;
+
+use super::{Attenuation, Light, LightSample};
+
+/// A point light restricted to a cone around `direction`. The light is at
+/// full strength within `inner_angle` of the axis and fades smoothly to
+/// nothing at `outer_angle` (both measured from the axis, in radians).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpotLight {
+    intensity: Tuple,
+    position: Tuple,
+    direction: Tuple,
+    cos_inner: f64,
+    cos_outer: f64,
+    attenuation: Attenuation,
+}
+
+impl SpotLight {
+    pub fn new(
+        intensity: Tuple,
+        position: Tuple,
+        direction: Tuple,
+        inner_angle: f64,
+        outer_angle: f64,
+    ) -> SpotLight {
+        SpotLight {
+            intensity,
+            position,
+            direction: direction.normalized(),
+            cos_inner: inner_angle.min(outer_angle).cos(),
+            cos_outer: outer_angle.cos(),
+            attenuation: Attenuation::None,
+        }
+    }
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> SpotLight {
+        self.attenuation = attenuation;
+        self
+    }
+    pub fn intensity(&self) -> &Tuple {
+        &self.intensity
+    }
+    pub fn position(&self) -> &Tuple {
+        &self.position
+    }
+    pub fn direction(&self) -> &Tuple {
+        &self.direction
+    }
+
+    /// How much of the light reaches `point`, from `0.0` outside the cone to
+    /// `1.0` inside the inner cone. The light's own position has no
+    /// direction, so it counts as lit.
+    pub fn falloff(&self, point: Tuple) -> f64 {
+        let to_point = point - self.position;
+        if to_point.magnitude() == 0.0 {
+            return 1.0;
+        }
+        let cos_angle = to_point.normalized().dot(&self.direction);
+        if cos_angle >= self.cos_inner {
+            return 1.0;
+        }
+        if cos_angle <= self.cos_outer {
+            return 0.0;
+        }
+        let x = (cos_angle - self.cos_outer) / (self.cos_inner - self.cos_outer);
+        x * x * (3.0 - 2.0 * x)
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "spot"))]
+impl Light for SpotLight {
+    fn intensity_at(&self, point: Tuple) -> Tuple {
+        self.intensity * self.falloff(point)
+    }
+
+    fn samples(&self, point: Tuple) -> Vec<LightSample> {
+        vec![LightSample::towards(point, self.position)]
+    }
+
+    fn attenuation(&self) -> Attenuation {
+        self.attenuation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        light::Light,
+        transformation::PI,
+        tuples::{
+            helpers::{color, point, vector},
+            FEquals,
+        },
+    };
+
+    use super::SpotLight;
+
+    fn setup() -> SpotLight {
+        SpotLight::new(
+            color(1, 1, 1),
+            point(0, 10, 0),
+            vector(0, -1, 0),
+            PI / 8.0,
+            PI / 4.0,
+        )
+    }
+
+    #[test]
+    fn full_strength_inside_inner_cone() {
+        let light = setup();
+        assert!(light.falloff(point(0, 0, 0)).eps_eq(1.0));
+        assert!(light.falloff(point(3, 0, 0)).eps_eq(1.0));
+        assert_eq!(light.intensity_at(point(0, 0, 0)), color(1, 1, 1));
+    }
+
+    #[test]
+    fn dark_outside_outer_cone() {
+        let light = setup();
+        assert!(light.falloff(point(11, 0, 0)).eps_eq(0.0));
+        assert!(light.falloff(point(0, 20, 0)).eps_eq(0.0));
+        assert_eq!(light.intensity_at(point(11, 0, 0)), color(0, 0, 0));
+    }
+
+    #[test]
+    fn full_strength_at_the_light() {
+        let light = setup();
+        assert_eq!(light.falloff(point(0, 10, 0)), 1.0);
+        assert_eq!(light.intensity_at(point(0, 10, 0)), color(1, 1, 1));
+    }
+
+    #[test]
+    fn smooth_between_cones() {
+        let light = setup();
+        let falloffs: Vec<_> = [5.0, 6.0, 7.0, 8.0, 9.0]
+            .iter()
+            .map(|&x| light.falloff(point(x, 0, 0)))
+            .collect();
+        assert!(falloffs.iter().all(|f| (0.0..=1.0).contains(f)));
+        assert!(falloffs.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn samples_like_point_light() {
+        let light = setup();
+        let samples = light.samples(point(0, 4, 0));
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].direction, vector(0, 1, 0));
+        assert!(samples[0].distance.eps_eq(6.0));
+    }
+}
@@ -1,433 +1,396 @@
-mod camera;
-mod canvas;
-mod light;
-mod material;
-mod matrix;
-mod patterns;
-mod ray;
-mod shapes;
-mod transformation;
-mod tuples;
-mod world;
-
-macro_rules! time {
-    ($b:block) => {{
-        let xx_timer_bb_dnsajkfdasb = std::time::Instant::now();
-        $b;
-        xx_timer_bb_dnsajkfdasb.elapsed()
-    }};
+//! Renders a YAML scene file (see `ray_tracing_rust::scene`) to an image.
+
+use std::{
+    env,
+    path::Path,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use image::ImageFormat;
+use ray_tracing_rust::{load_scene, parse_scene, PpmFormat, Scene};
+
+const USAGE: &str = "\
+Usage: ray_tracing_rust [OPTIONS] <SCENE>
+       ray_tracing_rust [OPTIONS] --demo <NAME>
+
+Renders a YAML scene file to an image.
+
+Options:
+  -o, --output <PATH>    Where to write the image [default: <scene name>.png]
+  -s, --size <WxH>       Overrides the resolution of the scene's camera
+      --samples <N>      Rays per pixel
+  -t, --threads <N>      Render threads [default: all cores]
+  -f, --format <FORMAT>  Image format (png, jpeg, bmp, ppm, ...) [default: from
+                         the output extension]
+      --demo <NAME>      Renders a built-in scene instead of a file
+      --list-demos       Lists the built-in scenes
+  -h, --help             Prints this help";
+
+/// Built-in scenes, selectable with `--demo`.
+const DEMOS: &[(&str, &str, &str)] = &[
+    (
+        "silhouette",
+        "A flattened sphere's silhouette (chapter 5)",
+        include_str!("../scenes/silhouette.yml"),
+    ),
+    (
+        "shaded",
+        "A single shaded sphere (chapter 6)",
+        include_str!("../scenes/shaded_sphere.yml"),
+    ),
+    (
+        "spheres",
+        "Three spheres in a room (chapter 7)",
+        include_str!("../scenes/spheres.yml"),
+    ),
+    (
+        "plane",
+        "Spheres on a reflective floor (chapters 9 and 11)",
+        include_str!("../scenes/plane.yml"),
+    ),
+];
+
+/// Other names for the demos, after the `pit_*` examples they reproduce.
+/// Chapters 1 to 4 don't render a scene, so they have no demo.
+const ALIASES: &[(&str, &str)] = &[
+    ("pit_05", "silhouette"),
+    ("pit_06", "shaded"),
+    ("pit_07", "spheres"),
+    ("pit_09", "plane"),
+];
+
+/// The source of the demo called `name`, or one of its aliases.
+fn demo(name: &str) -> Option<&'static str> {
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, demo)| demo);
+    DEMOS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, _, source)| *source)
 }
 
-fn main() {
-    println!("Hello World");
-    // pit_01::main();
-    // pit_02::main();
-    // pit_03::main();
-    // pit_04::main();
-    // pit_05::main();
-    // pit_06::main();
-    // pit_07::main();
-    pit_09::main();
+#[derive(Debug, PartialEq)]
+enum Input {
+    File(String),
+    Demo(String),
 }
 
-mod pit_01 {
-    use crate::tuples::{
-        helpers::{point, vector},
-        Tuple,
-    };
-
-    fn tick(env: (Tuple, Tuple), proj: (Tuple, Tuple)) -> (Tuple, Tuple) {
-        let position = proj.0 + proj.1;
-        let velocity = proj.1 + env.0 + env.1;
-        return (position, velocity);
-    }
+#[derive(Debug, PartialEq)]
+struct Args {
+    input: Input,
+    output: Option<String>,
+    size: Option<(usize, usize)>,
+    samples: Option<usize>,
+    threads: Option<usize>,
+    format: Option<ImageFormat>,
+}
 
-    pub fn main() {
-        let mut p = (point(0, 1, 0), vector(1, 1, 0).normalized());
-        let e = (vector(0, -0.1, 0), vector(-0.01, 0, 0));
+#[derive(Debug, PartialEq)]
+enum Command {
+    Render(Args),
+    ListDemos,
+    Help,
+}
 
-        let mut ticker = 0;
-        loop {
-            ticker += 1;
-            if p.0.y() <= 0.0 {
-                break;
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let mut input = None;
+    let mut output = None;
+    let mut size = None;
+    let mut samples = None;
+    let mut threads = None;
+    let mut format = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("`{name}` needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--list-demos" => return Ok(Command::ListDemos),
+            "-o" | "--output" => output = Some(value(&arg)?),
+            "-s" | "--size" => size = Some(parse_size(&value(&arg)?)?),
+            "--samples" => samples = Some(parse_count(&arg, &value(&arg)?)?),
+            "-t" | "--threads" => threads = Some(parse_count(&arg, &value(&arg)?)?),
+            "-f" | "--format" => {
+                let name = value(&arg)?;
+                format = Some(
+                    ImageFormat::from_extension(&name)
+                        .ok_or_else(|| format!("Unknown image format `{name}`"))?,
+                );
             }
-            p = tick(e, p);
-
-            println!("Tick {ticker} at {:?}", p.0);
+            "--demo" => set_input(&mut input, Input::Demo(value(&arg)?))?,
+            _ if arg.starts_with('-') => return Err(format!("Unknown option `{arg}`")),
+            _ => set_input(&mut input, Input::File(arg))?,
         }
-
-        println!("It took {ticker} Ticks.")
     }
-}
 
-mod pit_02 {
-    use crate::{
-        canvas::Canvas,
-        tuples::{
-            helpers::{color, point, vector},
-            Tuple,
-        },
-    };
+    let input = input.ok_or("No scene given")?;
+    Ok(Command::Render(Args {
+        input,
+        output,
+        size,
+        samples,
+        threads,
+        format,
+    }))
+}
 
-    fn tick(env: (Tuple, Tuple), proj: (Tuple, Tuple)) -> (Tuple, Tuple) {
-        let position = proj.0 + proj.1;
-        let velocity = proj.1 + env.0 + env.1;
-        return (position, velocity);
+fn set_input(input: &mut Option<Input>, new: Input) -> Result<(), String> {
+    if input.is_some() {
+        return Err("Only one scene can be rendered at a time".to_string());
     }
+    *input = Some(new);
+    Ok(())
+}
 
-    pub fn main() {
-        let start = point(0, 1, 0);
-        let velocity = vector(1, 1.8, 0).normalized() * 11.25;
-
-        let mut p = (start, velocity);
-
-        let gravity = vector(0, -0.1, 0);
-        let wind = vector(-0.01, 0, 0);
-
-        let e = (gravity, wind);
+fn parse_count(name: &str, value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("`{name}` needs a positive number, got `{value}`")),
+    }
+}
 
-        let mut c = Canvas::new(900, 550);
+fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    let error = || format!("Expected a size like `800x600`, got `{value}`");
+    let (w, h) = value.split_once('x').ok_or_else(error)?;
+    match (w.parse(), h.parse()) {
+        (Ok(w), Ok(h)) if w > 0 && h > 0 => Ok((w, h)),
+        _ => Err(error()),
+    }
+}
 
-        loop {
-            if p.0.y() <= 0.0 {
-                break;
+impl Args {
+    fn load(&self) -> Result<Scene, String> {
+        match &self.input {
+            Input::File(path) => load_scene(path).map_err(|e| format!("{path}: {e}")),
+            Input::Demo(name) => {
+                let source = demo(name)
+                    .ok_or_else(|| format!("Unknown demo `{name}`, see `--list-demos`"))?;
+                parse_scene(source).map_err(|e| format!("Demo `{name}`: {e}"))
             }
-
-            let x = p.0.x().round() as usize;
-            let y = 550 - p.0.y().round() as usize;
-
-            c[(x, y)] = color(1, 0, 0);
-
-            p = tick(e, p);
         }
+    }
 
-        c.save("./temp/pit_02.png").expect("Unable to save file");
+    /// The output path and format: both as given, or one derived from the
+    /// other, falling back to a PNG named after the scene.
+    fn output(&self) -> Result<(String, ImageFormat), String> {
+        let stem = match &self.input {
+            Input::File(path) => Path::new(path)
+                .file_stem()
+                .map_or("render".into(), |s| s.to_string_lossy()),
+            Input::Demo(name) => name.into(),
+        };
+
+        match (&self.output, self.format) {
+            (Some(path), Some(format)) => Ok((path.clone(), format)),
+            (Some(path), None) => {
+                let format = ImageFormat::from_path(path).map_err(|_| {
+                    format!("Can't tell the image format of `{path}`, use `--format`")
+                })?;
+                Ok((path.clone(), format))
+            }
+            (None, Some(ImageFormat::Pnm)) => Ok((format!("{stem}.ppm"), ImageFormat::Pnm)),
+            (None, Some(format)) => {
+                let extension = format.extensions_str().first().unwrap_or(&"img");
+                Ok((format!("{stem}.{extension}"), format))
+            }
+            (None, None) => Ok((format!("{stem}.png"), ImageFormat::Png)),
+        }
     }
 }
 
-mod pit_03 {
-    use crate::matrix::helpers::Mat4;
-
-    pub fn main() {
-        let a: Mat4 = ((3, -9, 7, 2), (3, -8, 2, -9), (-4, 4, 4, 1), (-6, 5, -1, 1)).into();
-        // let a: Mat2 = ((3, -9), (3, -8)).into();
-        println!("{a:?}");
-
-        println!("Invert of identity:\n{:?}", Mat4::identity());
-        println!("A * inv(A) =\n{:?}", a.clone() * a.inverse());
-        println!(
-            "{:?}=\n{:?}",
-            a.inverse().transpose(),
-            a.transpose().inverse()
-        );
+fn render(args: &Args) -> Result<(), String> {
+    let (output, format) = args.output()?;
+    let Scene { world, mut camera } = args.load()?;
+    if let Some((w, h)) = args.size {
+        camera.set_size(w, h);
+    }
+    if let Some(samples) = args.samples {
+        camera.set_samples(samples);
+    }
+    if let Some(threads) = args.threads {
+        camera.set_threads(threads);
     }
-}
 
-mod pit_04 {
-    use crate::{
-        canvas::Canvas,
-        transformation::rotation_y,
-        tuples::helpers::{colors, point},
+    println!(
+        "Rendering {}x{} with {} sample(s) per pixel on {} thread(s): {} object(s), {} light(s)",
+        camera.hsize(),
+        camera.vsize(),
+        camera.samples(),
+        camera.threads(),
+        world.objetcs().len(),
+        world.lights().len(),
+    );
+
+    let timer = Instant::now();
+    let (canvas, stats) = camera.render_with_stats(&world);
+    let took = timer.elapsed();
+
+    // PNM output is always a binary PPM, written without the image crate.
+    let saved = match format {
+        ImageFormat::Pnm => canvas
+            .save_ppm(&output, PpmFormat::Binary)
+            .map_err(|e| e.to_string()),
+        _ => canvas
+            .save_with_format(&output, format)
+            .map_err(|e| e.to_string()),
     };
+    saved.map_err(|e| format!("Failed to write `{output}`: {e}"))?;
+
+    println!(
+        "Rendered in {} ({} camera rays, {:.0} rays/s), saved to {output}",
+        format_duration(took),
+        stats.total_rays(),
+        stats.total_rays() as f64 / took.as_secs_f64().max(1e-9),
+    );
+    Ok(())
+}
 
-    pub fn main() {
-        let mut c = Canvas::new(100, 100);
-
-        for i in 0..12 {
-            let p = point(0, 0, 45);
-            let rot = rotation_y((30.0 * i as f64).to_radians());
-            let np = rot * p;
-
-            let x = 50.0 + np.x();
-            let y = 50.0 + np.z();
-
-            c[(x as usize, y as usize)] = colors::white();
-        }
-
-        c.save("./temp/pit_04.png").unwrap();
+fn format_duration(d: Duration) -> String {
+    if d.as_secs() >= 60 {
+        format!("{}m {:.1}s", d.as_secs() / 60, d.as_secs_f64() % 60.0)
+    } else {
+        format!("{:.2}s", d.as_secs_f64())
     }
 }
 
-mod pit_05 {
-
-    use crate::{
-        canvas::Canvas,
-        ray::{Intersections, Ray},
-        shapes::{Shape, Sphere},
-        transformation::scaling,
-        tuples::helpers::{color, point},
+fn main() -> ExitCode {
+    let command = match parse_args(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
     };
 
-    pub fn main() {
-        let ray_origin = point(0, 0, -5);
-        let wall_z = 10.0;
-        let wall_size = 7.0;
-
-        let canvas_pixel = 800;
-
-        let pixel_size = wall_size / canvas_pixel as f64;
-
-        let half = wall_size / 2.0;
-
-        let mut canvas = Canvas::new(canvas_pixel, canvas_pixel);
-        let color = color(1, 0, 0);
-
-        let mut shape = Sphere::new();
-        shape.set_transform(scaling(1.0, 0.5, 1.0));
-
-        for y in 0..canvas_pixel - 1 {
-            let world_y = half - pixel_size as f64 * y as f64;
-
-            for x in 0..canvas_pixel - 1 {
-                let world_x = -half + pixel_size as f64 * x as f64;
-
-                let position = point(world_x, world_y, wall_z);
-
-                let r = Ray::new(ray_origin, (position - ray_origin).normalized());
-                let xs = shape.intersect(&r);
-
-                match xs.hit() {
-                    Some(_) => canvas[(x, y)] = color,
-                    None => {}
+    match command {
+        Command::Help => println!("{USAGE}"),
+        Command::ListDemos => {
+            for (name, description, _) in DEMOS {
+                let aliases: Vec<_> = ALIASES
+                    .iter()
+                    .filter(|(_, demo)| demo == name)
+                    .map(|(alias, _)| *alias)
+                    .collect();
+                if aliases.is_empty() {
+                    println!("{name:10} {description}");
+                } else {
+                    println!("{name:10} {description}, also `{}`", aliases.join("`, `"));
                 }
             }
         }
-
-        canvas.save("./temp/pit_05.png").unwrap();
+        Command::Render(args) => {
+            if let Err(e) = render(&args) {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
     }
+    ExitCode::SUCCESS
 }
 
-mod pit_06 {
-
-    use std::rc::Rc;
-
-    use crate::{
-        canvas::Canvas,
-        light::PointLight,
-        material::Material,
-        ray::{Intersections, Ray},
-        shapes::{Shape, Sphere},
-        tuples::helpers::{color, point},
-    };
-
-    pub fn main() {
-        let ray_origin = point(0, 0, -5);
-        let wall_z = 10.0;
-        let wall_size = 7.0;
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+    use ray_tracing_rust::parse_scene;
 
-        let canvas_pixel = 800;
+    use super::{demo, parse_args, Args, Command, Input, ALIASES, DEMOS};
 
-        let pixel_size = wall_size / canvas_pixel as f64;
-
-        let half = wall_size / 2.0;
-
-        let mut canvas = Canvas::new(canvas_pixel, canvas_pixel);
-
-        let mut shape = Sphere::new();
-        // shape.set_transform(scaling(1.0, 0.5, 1.0));
-        let mut m = Material::default();
-        m.color = color(1, 0.2, 1).solid();
-        shape.set_material(m);
-
-        let light = PointLight::new(color(1, 1, 1), point(-10, 10, -10));
-
-        let time = time!({
-            for y in 0..canvas_pixel - 1 {
-                let world_y = half - pixel_size as f64 * y as f64;
-
-                for x in 0..canvas_pixel - 1 {
-                    let world_x = -half + pixel_size as f64 * x as f64;
-
-                    let position = point(world_x, world_y, wall_z);
-
-                    let r = Ray::new(ray_origin, (position - ray_origin).normalized());
-                    let xs = shape.intersect(&r);
-
-                    match xs.hit() {
-                        Some(hit) => {
-                            let point = r.at(hit.t());
-                            let normal = hit.object().normal_at(point);
-                            let eye = -r.direction();
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
 
-                            let color = hit
-                                .object()
-                                .material()
-                                .lighting(&shape, &light, point, eye, normal, false);
+    fn render_args(args: &[&str]) -> Args {
+        match parse(args) {
+            Ok(Command::Render(args)) => args,
+            other => panic!("Expected render arguments, got {other:?}"),
+        }
+    }
 
-                            canvas[(x, y)] = color;
-                        }
-                        None => {}
-                    }
-                }
+    #[test]
+    fn parsing_all_options() {
+        let args = render_args(&[
+            "scene.yml",
+            "-o",
+            "out.bmp",
+            "--size",
+            "320x200",
+            "--samples",
+            "4",
+            "-t",
+            "2",
+            "--format",
+            "png",
+        ]);
+        assert_eq!(
+            args,
+            Args {
+                input: Input::File("scene.yml".into()),
+                output: Some("out.bmp".into()),
+                size: Some((320, 200)),
+                samples: Some(4),
+                threads: Some(2),
+                format: Some(ImageFormat::Png),
             }
-        });
-
-        println!("{:#?}", time);
-        canvas.save("./temp/pit_06.png").unwrap();
+        );
+        assert_eq!(parse(&["--list-demos"]), Ok(Command::ListDemos));
+        assert_eq!(parse(&["x.yml", "--help"]), Ok(Command::Help));
     }
-}
 
-mod pit_07 {
-    use std::{rc::Rc, time::Instant};
-
-    use crate::{
-        camera::Camera,
-        light::PointLight,
-        material::Material,
-        shapes::{Shape, Sphere},
-        transformation::{helper::TransformationBuilder, scaling, translation, view_transform, PI},
-        tuples::helpers::{color, point, vector},
-        world::World,
-    };
+    #[test]
+    fn invalid_arguments() {
+        for args in [
+            &[][..],
+            &["scene.yml", "--size", "320"],
+            &["scene.yml", "--size", "0x10"],
+            &["scene.yml", "--samples", "none"],
+            &["scene.yml", "--threads", "0"],
+            &["scene.yml", "--format", "doc"],
+            &["scene.yml", "--output"],
+            &["scene.yml", "--verbose"],
+            &["a.yml", "b.yml"],
+            &["a.yml", "--demo", "plane"],
+        ] {
+            assert!(parse(args).is_err(), "{args:?} should be rejected");
+        }
+    }
 
-    pub fn main() {
-        let mut floor = Sphere::new();
-        floor.set_transform(scaling(10.0, 0.01, 10.0));
-        floor.material_mut().color = color(1, 0.9, 0.9).solid();
-        floor.material_mut().specular = 0.0;
-
-        let mut left_wall = Sphere::new();
-        left_wall.set_transform(
-            TransformationBuilder::create()
-                .scaling(10.0, 0.01, 10.0)
-                .rotation_x(PI / 2.0)
-                .rotation_y(-PI / 4.0)
-                .translation(0.0, 0.0, 5.0)
-                .build(),
-        );
-        left_wall.set_material(Material::clone(&floor.material()));
-
-        let mut right_wall = Sphere::new();
-        right_wall.set_transform(
-            TransformationBuilder::create()
-                .scaling(10.0, 0.01, 10.0)
-                .rotation_x(PI / 2.0)
-                .rotation_y(PI / 4.0)
-                .translation(0.0, 0.0, 5.0)
-                .build(),
+    #[test]
+    fn output_path_and_format() {
+        let output = |args: &[&str]| render_args(args).output();
+        assert_eq!(
+            output(&["scenes/room.yml"]),
+            Ok(("room.png".into(), ImageFormat::Png))
         );
-        right_wall.set_material(Material::clone(&floor.material().clone()));
-
-        let mut middle = Sphere::new();
-        middle.set_transform(translation(-0.5, 1.0, 0.5));
-        middle.material_mut().color = color(0.1, 1.0, 0.5).solid();
-        middle.material_mut().diffuse = 0.7;
-        middle.material_mut().specular = 0.3;
-
-        let mut right = Sphere::new();
-        right.set_transform(
-            TransformationBuilder::create()
-                .scaling(0.5, 0.5, 0.5)
-                .translation(1.5, 0.5, -0.5)
-                .build(),
+        assert_eq!(
+            output(&["--demo", "plane", "-f", "jpeg"]),
+            Ok(("plane.jpg".into(), ImageFormat::Jpeg))
         );
-        right.material_mut().color = color(0.5, 1.0, 0.1).solid();
-        right.material_mut().diffuse = 0.7;
-        right.material_mut().specular = 0.3;
-
-        let mut left = Sphere::new();
-        left.set_transform(
-            TransformationBuilder::create()
-                .scaling(0.33, 0.33, 0.33)
-                .translation(-1.5, 0.33, -0.75)
-                .build(),
+        assert_eq!(
+            output(&["a.yml", "-o", "x.bmp"]),
+            Ok(("x.bmp".into(), ImageFormat::Bmp))
         );
-        left.material_mut().color = color(1, 0.8, 0.1).solid();
-        left.material_mut().diffuse = 0.7;
-        left.material_mut().specular = 0.3;
-
-        let mut world = World::new();
-        world.objetcs_mut().push(Box::new(floor));
-        world.objetcs_mut().push(Box::new(left_wall));
-        world.objetcs_mut().push(Box::new(right_wall));
-        world.objetcs_mut().push(Box::new(middle));
-        world.objetcs_mut().push(Box::new(left));
-        world.objetcs_mut().push(Box::new(right));
-
-        world.set_light(Some(PointLight::new(color(1, 1, 1), point(-10, 10, -10))));
-
-        let camera = Camera::new_transformed(
-            1920,
-            1080,
-            PI / 3.0,
-            view_transform(point(0, 1.5, -5), point(0, 1, 0), vector(0, 1, 0)),
+        assert_eq!(
+            output(&["--demo", "spheres", "-f", "ppm"]),
+            Ok(("spheres.ppm".into(), ImageFormat::Pnm))
         );
-
-        let timer = Instant::now();
-        let canvas = camera.render(&world);
-        let took = timer.elapsed();
-
-        canvas.save("./temp/pit_07.png").unwrap();
-        println!("Rendering took: {:#?}", took);
+        assert!(output(&["a.yml", "-o", "x.unknown"]).is_err());
     }
-}
-
-mod pit_09 {
-    use std::{rc::Rc, time::Instant};
-
-    use crate::{
-        camera::Camera,
-        light::PointLight,
-        shapes::{Plane, Shape, Sphere},
-        transformation::{helper::TransformationBuilder, translation, view_transform, PI},
-        tuples::helpers::{color, point, vector},
-        world::World,
-    };
 
-    pub fn main() {
-        let mut floor = Plane::new();
-        floor.material_mut().color = color(1, 0.9, 0.9).solid();
-        floor.material_mut().specular = 0.0;
-
-        let mut middle = Sphere::new();
-        middle.set_transform(translation(-0.5, 1.0, 0.5));
-        middle.material_mut().color = color(0.1, 1.0, 0.5).solid();
-        middle.material_mut().diffuse = 0.7;
-        middle.material_mut().specular = 0.3;
-
-        let mut right = Sphere::new();
-        right.set_transform(
-            TransformationBuilder::create()
-                .scaling(0.5, 0.5, 0.5)
-                .translation(1.5, 0.5, -0.5)
-                .build(),
-        );
-        right.material_mut().color = color(0.5, 1.0, 0.1).solid();
-        right.material_mut().diffuse = 0.7;
-        right.material_mut().specular = 0.3;
-
-        let mut left = Sphere::new();
-        left.set_transform(
-            TransformationBuilder::create()
-                .scaling(0.33, 0.33, 0.33)
-                .translation(-1.5, 0.33, -0.75)
-                .build(),
-        );
-        left.material_mut().color = color(1, 0.8, 0.1).solid();
-        left.material_mut().diffuse = 0.7;
-        left.material_mut().specular = 0.3;
-
-        let mut world = World::new();
-        world.objetcs_mut().push(Box::new(floor));
-        world.objetcs_mut().push(Box::new(middle));
-        world.objetcs_mut().push(Box::new(left));
-        world.objetcs_mut().push(Box::new(right));
-
-        world.set_light(Some(PointLight::new(color(1, 1, 1), point(-10, 10, -10))));
-
-        let camera = Camera::new_transformed(
-            1920,
-            1080,
-            PI / 3.0,
-            view_transform(point(0, 1.5, -5), point(0, 1, 0), vector(0, 1, 0)),
-        );
-
-        let timer = Instant::now();
-        let canvas = camera.render(&world);
-        let took = timer.elapsed();
-
-        canvas.save("./temp/pit_09.png").unwrap();
-        println!("Rendering took: {:#?}", took);
+    #[test]
+    fn demos_load() {
+        for (name, _, source) in DEMOS {
+            assert!(parse_scene(source).is_ok(), "demo {name}");
+        }
+        for (alias, name) in ALIASES {
+            assert_eq!(demo(alias), demo(name), "alias {alias}");
+            assert!(demo(alias).is_some(), "alias {alias}");
+        }
+        let scene = render_args(&["--demo", "pit_07"]).load().unwrap();
+        assert_eq!(scene.world.objetcs().len(), 6);
+        assert!(render_args(&["--demo", "nope"]).load().is_err());
+        assert!(render_args(&["missing.yml"]).load().is_err());
     }
 }
@@ -1,7 +1,7 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{
-    light::PointLight,
+    light::Light,
     patterns::Pattern,
     shapes::Shape,
     tuples::{
@@ -11,12 +11,16 @@ use crate::{
 };
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Material {
-    pub color: Rc<dyn Pattern>,
+    pub color: Arc<dyn Pattern>,
     pub ambient: f64,
     pub diffuse: f64,
     pub specular: f64,
     pub shininess: f64,
+    pub reflective: f64,
+    pub transparency: f64,
+    pub refractive_index: f64,
 }
 
 impl Default for Material {
@@ -27,6 +31,9 @@ impl Default for Material {
             diffuse: 0.9,
             specular: 0.9,
             shininess: 200.0,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
         }
     }
 }
@@ -37,10 +44,20 @@ impl PartialEq for Material {
             && self.diffuse == other.diffuse
             && self.specular == other.specular
             && self.shininess == other.shininess
+            && self.reflective == other.reflective
+            && self.transparency == other.transparency
+            && self.refractive_index == other.refractive_index
+    }
+}
+
+impl Clone for Material {
+    fn clone(&self) -> Self {
+        Material::clone(self)
     }
 }
 
 impl Material {
+    #[allow(clippy::should_implement_trait)]
     pub fn clone(m: &Material) -> Material {
         Material {
             color: m.color.clone(),
@@ -48,59 +65,80 @@ impl Material {
             diffuse: m.diffuse,
             specular: m.specular,
             shininess: m.shininess,
+            reflective: m.reflective,
+            transparency: m.transparency,
+            refractive_index: m.refractive_index,
         }
     }
+    /// Phong shading of `point` lit by `light`. `light_intensity` is the
+    /// fraction of the light reaching the point (`0.0` in full shadow); for
+    /// area lights, diffuse and specular are averaged over the light's samples.
+    /// The light's attenuation scales diffuse and specular, but not ambient.
     pub fn lighting(
         &self,
         object: &dyn Shape,
-        light: &PointLight,
+        light: &dyn Light,
         point: Tuple,
         eyev: Tuple,
         normalv: Tuple,
-        in_shadow: bool,
+        light_intensity: f64,
     ) -> Tuple {
-        let effective_color = self.color.at_object(object, &point) * *light.intensity();
+        let surface = self.color.at_object(object, &point);
+        self.lighting_with_color(surface, light, point, eyev, normalv, light_intensity)
+    }
 
-        let lightv = (*light.position() - point).normalized();
+    /// Like `lighting`, with the surface color already looked up (e.g. for
+    /// a moving object, where it depends on the time of the ray).
+    pub fn lighting_with_color(
+        &self,
+        surface: Tuple,
+        light: &dyn Light,
+        point: Tuple,
+        eyev: Tuple,
+        normalv: Tuple,
+        light_intensity: f64,
+    ) -> Tuple {
+        let intensity = light.intensity_at(point);
+        let effective_color = surface * intensity;
 
         let ambient = effective_color * self.ambient;
 
-        let light_dot_normal = lightv.dot(&normalv);
+        let samples = light.samples(point);
+        if samples.is_empty() {
+            return ambient;
+        }
+        let mut sum = colors::black();
+        for sample in &samples {
+            let lightv = sample.direction;
+            let light_dot_normal = lightv.dot(&normalv);
+            if light_dot_normal < 0.0 {
+                continue;
+            }
+
+            let attenuation = light.attenuation().factor(sample.distance);
+            sum = sum + effective_color * self.diffuse * light_dot_normal * attenuation;
 
-        let (diffuse, specular) = if light_dot_normal < 0.0 {
-            (colors::black(), colors::black())
-        } else {
-            let diffuse = effective_color * self.diffuse * light_dot_normal;
             let reflectv = (-lightv).reflect(normalv);
             let reflect_dot_eye = reflectv.dot(&eyev);
-
-            let specular = if reflect_dot_eye <= 0.0 {
-                colors::black()
-            } else {
+            if reflect_dot_eye > 0.0 {
                 let factor = reflect_dot_eye.powf(self.shininess);
-                *light.intensity() * self.specular * factor
-            };
-
-            (diffuse, specular)
-        };
-
-        ambient
-            + if in_shadow {
-                colors::black()
-            } else {
-                diffuse + specular
+                sum = sum + intensity * self.specular * factor * attenuation;
             }
+        }
+
+        ambient + sum / samples.len() as f64 * light_intensity
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::{
-        light::PointLight,
+        light::{AreaLight, Attenuation, DirectionalLight, PointLight, SpotLight},
         patterns::StripePattern,
         shapes::Sphere,
+        transformation::PI,
         tuples::{
             helpers::{color, colors, point, vector},
             FEquals, Tuple,
@@ -122,6 +160,9 @@ mod tests {
         assert!(m.diffuse.eps_eq(0.9));
         assert!(m.specular.eps_eq(0.9));
         assert!(m.shininess.eps_eq(200.0));
+        assert!(m.reflective.eps_eq(0.0));
+        assert!(m.transparency.eps_eq(0.0));
+        assert!(m.refractive_index.eps_eq(1.0));
     }
 
     #[test]
@@ -133,7 +174,7 @@ mod tests {
         let normalv = vector(0, 0, -1);
         let light = PointLight::new(color(1, 1, 1), point(0, 0, -10));
 
-        let result = m.lighting(&s, &light, position, eyev, normalv, false);
+        let result = m.lighting(&s, &light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(1.9, 1.9, 1.9));
     }
 
@@ -146,7 +187,7 @@ mod tests {
         let normalv = vector(0, 0, -1);
         let light = PointLight::new(color(1, 1, 1), point(0, 0, -10));
 
-        let result = m.lighting(&s, &light, position, eyev, normalv, false);
+        let result = m.lighting(&s, &light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(1.0, 1.0, 1.0));
     }
 
@@ -159,7 +200,7 @@ mod tests {
         let normalv = vector(0, 0, -1);
         let light = PointLight::new(color(1, 1, 1), point(0, 10, -10));
 
-        let result = m.lighting(&s, &light, position, eyev, normalv, false);
+        let result = m.lighting(&s, &light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(0.7364, 0.7364, 0.7364));
     }
 
@@ -172,7 +213,7 @@ mod tests {
         let normalv = vector(0, 0, -1);
         let light = PointLight::new(color(1, 1, 1), point(0, 10, -10));
 
-        let result = m.lighting(&s, &light, position, eyev, normalv, false);
+        let result = m.lighting(&s, &light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(1.6364, 1.6364, 1.6364));
     }
 
@@ -185,7 +226,7 @@ mod tests {
         let normalv = vector(0, 0, -1);
         let light = PointLight::new(color(1, 1, 1), point(0, 0, 10));
 
-        let result = m.lighting(&s, &light, position, eyev, normalv, false);
+        let result = m.lighting(&s, &light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(0.1, 0.1, 0.1));
     }
 
@@ -197,17 +238,17 @@ mod tests {
         let eyev = vector(0, 0, -1);
         let normalv = vector(0, 0, -1);
         let light = PointLight::new(color(1, 1, 1), point(0, 0, -10));
-        let in_shadow = true;
-        let result = m.lighting(&s, &light, position, eyev, normalv, in_shadow);
+        let result = m.lighting(&s, &light, position, eyev, normalv, 0.0);
         assert_eq!(result, color(0.1, 0.1, 0.1));
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn lighting_with_pattern() {
         let (mut m, position) = setup();
         let s = Sphere::new();
 
-        m.color = Rc::new(StripePattern::new(colors::white(), colors::black()));
+        m.color = Arc::new(StripePattern::new(colors::white(), colors::black()));
         m.ambient = 1.0;
         m.diffuse = 0.0;
         m.specular = 0.0;
@@ -215,10 +256,129 @@ mod tests {
         let normalv = vector(0, 0, -1);
         let light = PointLight::new(colors::white(), point(0, 0, -10));
 
-        let c1 = m.lighting(&s, &light, point(0.9, 0, 0), eyev, normalv, false);
-        let c2 = m.lighting(&s, &light, point(1.1, 0, 0), eyev, normalv, false);
+        let c1 = m.lighting(&s, &light, point(0.9, 0, 0), eyev, normalv, 1.0);
+        let c2 = m.lighting(&s, &light, point(1.1, 0, 0), eyev, normalv, 1.0);
 
         assert_eq!(c1, colors::white());
         assert_eq!(c2, colors::black());
     }
+
+    #[test]
+    fn lighting_scales_with_light_intensity() {
+        let m = Material {
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.0,
+            ..Default::default()
+        };
+        let s = Sphere::new();
+        let light = PointLight::new(colors::white(), point(0, 0, -10));
+        let (pt, eyev, normalv) = (point(0, 0, -1), vector(0, 0, -1), vector(0, 0, -1));
+
+        let cases = [(1.0, 1.0), (0.5, 0.55), (0.0, 0.1)];
+        for (intensity, expected) in cases {
+            let result = m.lighting(&s, &light, pt, eyev, normalv, intensity);
+            assert_eq!(result, color(expected, expected, expected));
+        }
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn lighting_samples_area_light() {
+        let light = AreaLight::new(
+            colors::white(),
+            point(-0.5, -0.5, -5),
+            vector(1, 0, 0),
+            2,
+            vector(0, 1, 0),
+            2,
+        );
+        let s = Sphere::new();
+        let m = Material {
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.0,
+            ..Default::default()
+        };
+        let eye = point(0, 0, -5);
+
+        let cases = [
+            (point(0, 0, -1), 0.9965),
+            (point(0, 0.7071, -0.7071), 0.62318),
+        ];
+        for (pt, expected) in cases {
+            let eyev = (eye - pt).normalized();
+            let normalv = vector(pt.x(), pt.y(), pt.z());
+            let result = m.lighting(&s, &light, pt, eyev, normalv, 1.0);
+            assert_eq!(result, color(expected, expected, expected));
+        }
+    }
+
+    #[test]
+    fn lighting_with_directional_light() {
+        let (m, position) = setup();
+        let s = Sphere::new();
+        let light = DirectionalLight::new(color(1, 1, 1), vector(0, 0, 1));
+
+        let result = m.lighting(
+            &s,
+            &light,
+            position,
+            vector(0, 0, -1),
+            vector(0, 0, -1),
+            1.0,
+        );
+        assert_eq!(result, color(1.9, 1.9, 1.9));
+    }
+
+    #[test]
+    fn lighting_outside_spot_cone() {
+        let (m, position) = setup();
+        let s = Sphere::new();
+        let light = SpotLight::new(
+            color(1, 1, 1),
+            point(0, 0, -10),
+            vector(0, 1, 0),
+            PI / 8.0,
+            PI / 4.0,
+        );
+
+        let result = m.lighting(
+            &s,
+            &light,
+            position,
+            vector(0, 0, -1),
+            vector(0, 0, -1),
+            1.0,
+        );
+        assert_eq!(result, color(0, 0, 0));
+    }
+
+    #[test]
+    fn lighting_with_attenuation() {
+        let (m, position) = setup();
+        let s = Sphere::new();
+        let (eyev, normalv) = (vector(0, 0, -1), vector(0, 0, -1));
+        let light = PointLight::new(color(1, 1, 1), point(0, 0, -10));
+
+        let plain = m.lighting(&s, &light, position, eyev, normalv, 1.0);
+        let none = light.with_attenuation(Attenuation::None);
+        assert_eq!(m.lighting(&s, &none, position, eyev, normalv, 1.0), plain);
+
+        let linear = light.with_attenuation(Attenuation::Linear);
+        let result = m.lighting(&s, &linear, position, eyev, normalv, 1.0);
+        assert_eq!(result, color(0.28, 0.28, 0.28));
+
+        let squared = light.with_attenuation(Attenuation::InverseSquare);
+        let result = m.lighting(&s, &squared, position, eyev, normalv, 1.0);
+        assert_eq!(result, color(0.118, 0.118, 0.118));
+
+        let coefficients = light.with_attenuation(Attenuation::Coefficients {
+            constant: 1.0,
+            linear: 0.0,
+            quadratic: 0.0,
+        });
+        let result = m.lighting(&s, &coefficients, position, eyev, normalv, 1.0);
+        assert_eq!(result, plain);
+    }
 }
@@ -5,12 +5,17 @@ use std::{
 
 use crate::tuples::{FEquals, Tuple};
 
+/// How small the determinant may get, relative to its largest possible value
+/// for the matrix's column lengths, before the matrix counts as singular.
+const SINGULAR: f64 = 1e-12;
+
 #[derive(Clone)]
 pub struct Matrix4x4 {
     m: [[f64; 4]; 4],
 }
 
 impl Matrix4x4 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         m00: f64,
         m01: f64,
@@ -60,6 +65,7 @@ pub struct Matrix3x3 {
 }
 
 impl Matrix3x3 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         m00: f64,
         m01: f64,
@@ -118,6 +124,29 @@ impl IndexMut<(usize, usize)> for Matrix2x2 {
     }
 }
 
+/// Matrices are written as a list of rows, the way they are printed.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Matrix4x4 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let rows: [[f64; 4]; 4] = std::array::from_fn(|r| std::array::from_fn(|c| self[(r, c)]));
+        rows.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Matrix4x4 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rows = <[[f64; 4]; 4]>::deserialize(deserializer)?;
+        let mut m = Matrix4x4::identity();
+        for (r, row) in rows.iter().enumerate() {
+            for (c, value) in row.iter().enumerate() {
+                m[(r, c)] = *value;
+            }
+        }
+        Ok(m)
+    }
+}
+
 impl PartialEq for Matrix4x4 {
     fn eq(&self, other: &Self) -> bool {
         for x in 0..4 {
@@ -163,6 +192,14 @@ impl PartialEq for Matrix2x2 {
 impl Mul for Matrix4x4 {
     type Output = Matrix4x4;
 
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul for &Matrix4x4 {
+    type Output = Matrix4x4;
+
     fn mul(self, rhs: Self) -> Self::Output {
         let mut m = Matrix4x4::zero();
 
@@ -182,6 +219,14 @@ impl Mul for Matrix4x4 {
 impl Mul<Tuple> for Matrix4x4 {
     type Output = Tuple;
 
+    fn mul(self, rhs: Tuple) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<Tuple> for &Matrix4x4 {
+    type Output = Tuple;
+
     fn mul(self, rhs: Tuple) -> Self::Output {
         Tuple::new(
             self[(0, 0)] * rhs.x()
@@ -294,7 +339,7 @@ impl Matrix4x4 {
     }
 
     pub fn cofactor(&self, row: usize, col: usize) -> f64 {
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             self.minor(row, col)
         } else {
             -self.minor(row, col)
@@ -308,13 +353,33 @@ impl Matrix4x4 {
             + self[(0, 3)] * self.cofactor(0, 3)
     }
 
+    /// Whether the matrix can be inverted reliably. The determinant is
+    /// measured against the product of the column lengths, which bounds it,
+    /// so small but well-formed scales like `scaling(0.02, 0.02, 0.02)` still
+    /// count as invertible.
     pub fn invertible(&self) -> bool {
-        !self.determinant().eps_eq(0.0)
+        let columns: f64 = (0..4)
+            .map(|col| {
+                (0..4)
+                    .map(|row| self[(row, col)].powi(2))
+                    .sum::<f64>()
+                    .sqrt()
+            })
+            .product();
+        self.determinant().abs() > SINGULAR * columns
     }
 
     pub fn inverse(&self) -> Matrix4x4 {
         debug_assert!(self.invertible());
+        self.inverse_unchecked()
+    }
 
+    /// The inverse, or `None` if the matrix isn't `invertible`.
+    pub fn try_inverse(&self) -> Option<Matrix4x4> {
+        self.invertible().then(|| self.inverse_unchecked())
+    }
+
+    fn inverse_unchecked(&self) -> Matrix4x4 {
         let det = self.determinant();
 
         Matrix4x4::new(
@@ -381,7 +446,7 @@ impl Matrix3x3 {
     }
 
     pub fn cofactor(&self, row: usize, col: usize) -> f64 {
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             self.minor(row, col)
         } else {
             -self.minor(row, col)
@@ -466,13 +531,12 @@ pub mod helpers {
     pub type Mat2 = Matrix2x2;
 
     impl<M00, M01, M02, M03, M10, M11, M12, M13, M20, M21, M22, M23, M30, M31, M32, M33>
-        Into<Matrix4x4>
-        for (
+        From<(
             (M00, M10, M20, M30),
             (M01, M11, M21, M31),
             (M02, M12, M22, M32),
             (M03, M13, M23, M33),
-        )
+        )> for Matrix4x4
     where
         M00: Into<f64>,
         M01: Into<f64>,
@@ -491,30 +555,37 @@ pub mod helpers {
         M32: Into<f64>,
         M33: Into<f64>,
     {
-        fn into(self) -> Matrix4x4 {
+        fn from(
+            m: (
+                (M00, M10, M20, M30),
+                (M01, M11, M21, M31),
+                (M02, M12, M22, M32),
+                (M03, M13, M23, M33),
+            ),
+        ) -> Matrix4x4 {
             Matrix4x4::new(
-                self.0 .0.into(),
-                self.0 .1.into(),
-                self.0 .2.into(),
-                self.0 .3.into(),
-                self.1 .0.into(),
-                self.1 .1.into(),
-                self.1 .2.into(),
-                self.1 .3.into(),
-                self.2 .0.into(),
-                self.2 .1.into(),
-                self.2 .2.into(),
-                self.2 .3.into(),
-                self.3 .0.into(),
-                self.3 .1.into(),
-                self.3 .2.into(),
-                self.3 .3.into(),
+                m.0 .0.into(),
+                m.0 .1.into(),
+                m.0 .2.into(),
+                m.0 .3.into(),
+                m.1 .0.into(),
+                m.1 .1.into(),
+                m.1 .2.into(),
+                m.1 .3.into(),
+                m.2 .0.into(),
+                m.2 .1.into(),
+                m.2 .2.into(),
+                m.2 .3.into(),
+                m.3 .0.into(),
+                m.3 .1.into(),
+                m.3 .2.into(),
+                m.3 .3.into(),
             )
         }
     }
 
-    impl<M00, M01, M02, M10, M11, M12, M20, M21, M22> Into<Matrix3x3>
-        for ((M00, M10, M20), (M01, M11, M21), (M02, M12, M22))
+    impl<M00, M01, M02, M10, M11, M12, M20, M21, M22>
+        From<((M00, M10, M20), (M01, M11, M21), (M02, M12, M22))> for Matrix3x3
     where
         M00: Into<f64>,
         M01: Into<f64>,
@@ -526,35 +597,30 @@ pub mod helpers {
         M21: Into<f64>,
         M22: Into<f64>,
     {
-        fn into(self) -> Matrix3x3 {
+        fn from(m: ((M00, M10, M20), (M01, M11, M21), (M02, M12, M22))) -> Matrix3x3 {
             Matrix3x3::new(
-                self.0 .0.into(),
-                self.0 .1.into(),
-                self.0 .2.into(),
-                self.1 .0.into(),
-                self.1 .1.into(),
-                self.1 .2.into(),
-                self.2 .0.into(),
-                self.2 .1.into(),
-                self.2 .2.into(),
+                m.0 .0.into(),
+                m.0 .1.into(),
+                m.0 .2.into(),
+                m.1 .0.into(),
+                m.1 .1.into(),
+                m.1 .2.into(),
+                m.2 .0.into(),
+                m.2 .1.into(),
+                m.2 .2.into(),
             )
         }
     }
 
-    impl<M00, M01, M10, M11> Into<Matrix2x2> for ((M00, M10), (M01, M11))
+    impl<M00, M01, M10, M11> From<((M00, M10), (M01, M11))> for Matrix2x2
     where
         M00: Into<f64>,
         M01: Into<f64>,
         M10: Into<f64>,
         M11: Into<f64>,
     {
-        fn into(self) -> Matrix2x2 {
-            Matrix2x2::new(
-                self.0 .0.into(),
-                self.0 .1.into(),
-                self.1 .0.into(),
-                self.1 .1.into(),
-            )
+        fn from(m: ((M00, M10), (M01, M11))) -> Matrix2x2 {
+            Matrix2x2::new(m.0 .0.into(), m.0 .1.into(), m.1 .0.into(), m.1 .1.into())
         }
     }
 
@@ -791,6 +857,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn invertibility_is_scale_aware() {
+        let small: Mat4 = (
+            (0.02, 0.0, 0.0, 0.0),
+            (0.0, 0.02, 0.0, 0.0),
+            (0.0, 0.0, 0.02, 0.0),
+            (0.0, 0.0, 0.0, 1.0),
+        )
+            .into();
+        assert!(small.invertible());
+        assert_eq!(&small * &small.try_inverse().unwrap(), Mat4::identity());
+
+        let flat: Mat4 = ((0, 0, 0, 0), (0, 1, 0, 0), (0, 0, 1, 0), (0, 0, 0, 1)).into();
+        assert!(!flat.invertible());
+        assert!(flat.try_inverse().is_none());
+
+        let sheared: Mat4 = ((1, 1, 0, 0), (1, 1, 0, 0), (0, 0, 1, 0), (0, 0, 0, 1)).into();
+        assert!(!sheared.invertible());
+    }
+
     #[test]
     fn inverse_4x4() {
         let a: Mat4 = ((-5, 2, 6, -8), (1, -5, 1, 8), (7, 7, -6, -7), (1, -3, 7, 4)).into();
@@ -860,4 +946,16 @@ mod tests {
         let a: Mat4 = ((3, -9, 7, 2), (3, -8, 2, -9), (-4, 4, 4, 1), (-6, 5, -1, 1)).into();
         println!("{a:?}");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn matrices_serialize_as_rows() {
+        let a: Mat4 = ((1, 2, 3, 4), (5, 6, 7, 8), (9, 8, 7, 6), (5, 4, 3, 2)).into();
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(
+            json,
+            "[[1.0,2.0,3.0,4.0],[5.0,6.0,7.0,8.0],[9.0,8.0,7.0,6.0],[5.0,4.0,3.0,2.0]]"
+        );
+        assert_eq!(serde_json::from_str::<Mat4>(&json).unwrap(), a);
+    }
 }
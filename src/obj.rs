@@ -0,0 +1,398 @@
+//! A loader for the subset of Wavefront OBJ files needed to render meshes:
+//! vertices (`v`), vertex normals (`vn`), faces (`f`) and groups (`g`).
+
+use std::{error::Error, fmt::Display, fs, io, path::Path};
+
+use crate::{
+    shapes::{Group, Mesh, Shape, SmoothTriangle, Triangle},
+    tuples::Tuple,
+};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjError::Io(e) => write!(f, "Failed to read OBJ file: {e}"),
+            ObjError::Parse { line, message } => write!(f, "Line {line}: {message}"),
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObjError::Io(e) => Some(e),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(value: io::Error) -> Self {
+        ObjError::Io(value)
+    }
+}
+
+/// A line the parser did not understand and skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnoredLine {
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ObjFile {
+    vertices: Vec<Tuple>,
+    normals: Vec<Tuple>,
+    default_group: Vec<Box<dyn Shape>>,
+    groups: Vec<(String, Vec<Box<dyn Shape>>)>,
+    ignored: Vec<IgnoredLine>,
+}
+
+impl ObjFile {
+    /// The vertex with the given 1-based index, as used by the file.
+    pub fn vertex(&self, index: usize) -> Option<Tuple> {
+        index
+            .checked_sub(1)
+            .and_then(|i| self.vertices.get(i).copied())
+    }
+    /// The normal with the given 1-based index, as used by the file.
+    pub fn normal(&self, index: usize) -> Option<Tuple> {
+        index
+            .checked_sub(1)
+            .and_then(|i| self.normals.get(i).copied())
+    }
+    pub fn vertices(&self) -> &[Tuple] {
+        &self.vertices
+    }
+    pub fn normals(&self) -> &[Tuple] {
+        &self.normals
+    }
+    /// Triangles of faces that appear before the first `g` statement.
+    pub fn default_group(&self) -> &[Box<dyn Shape>] {
+        &self.default_group
+    }
+    pub fn group(&self, name: &str) -> Option<&[Box<dyn Shape>]> {
+        self.groups
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, g)| g.as_slice())
+    }
+    pub fn group_names(&self) -> impl Iterator<Item = &str> {
+        self.groups.iter().map(|(n, _)| n.as_str())
+    }
+    pub fn ignored(&self) -> &[IgnoredLine] {
+        &self.ignored
+    }
+
+    /// Combines all groups into a single mesh that can be added to a `World`.
+    pub fn into_mesh(self) -> Mesh {
+        let mut triangles = self.default_group;
+        for (_, group) in self.groups {
+            triangles.extend(group);
+        }
+        Mesh::new(triangles)
+    }
+
+    /// Keeps the structure of the file: one mesh per non-empty group.
+    pub fn into_group(self) -> Group {
+        let mut group = Group::new();
+        let meshes =
+            std::iter::once(self.default_group).chain(self.groups.into_iter().map(|(_, g)| g));
+        for triangles in meshes.filter(|t| !t.is_empty()) {
+            group.add_child(Box::new(Mesh::new(triangles)));
+        }
+        group
+    }
+}
+
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<ObjFile, ObjError> {
+    parse_obj(&fs::read_to_string(path)?)
+}
+
+pub fn parse_obj(source: &str) -> Result<ObjFile, ObjError> {
+    let mut obj = ObjFile::default();
+    let mut current_group: Option<usize> = None;
+
+    for (i, text) in source.lines().enumerate() {
+        let line = i + 1;
+        let error = |message: String| ObjError::Parse { line, message };
+
+        let mut tokens = text.split_whitespace();
+        match tokens.next() {
+            None => {}
+            Some(t) if t.starts_with('#') => {}
+            Some("v") => obj
+                .vertices
+                .push(parse_xyz(tokens, Tuple::point).map_err(error)?),
+            Some("vn") => obj
+                .normals
+                .push(parse_xyz(tokens, Tuple::vector).map_err(error)?),
+            Some("f") => {
+                let corners = tokens
+                    .map(|t| parse_corner(t, &obj.vertices, &obj.normals))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                if corners.len() < 3 {
+                    return Err(error(format!(
+                        "A face needs at least 3 vertices, found {}",
+                        corners.len()
+                    )));
+                }
+
+                let triangles = fan_triangulation(&corners);
+                match current_group {
+                    Some(g) => obj.groups[g].1.extend(triangles),
+                    None => obj.default_group.extend(triangles),
+                }
+            }
+            Some("g") => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                current_group = Some(match obj.groups.iter().position(|(n, _)| *n == name) {
+                    Some(g) => g,
+                    None => {
+                        obj.groups.push((name, Vec::new()));
+                        obj.groups.len() - 1
+                    }
+                });
+            }
+            Some(_) => obj.ignored.push(IgnoredLine {
+                line,
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    Ok(obj)
+}
+
+fn parse_xyz<'a>(
+    mut tokens: impl Iterator<Item = &'a str>,
+    make: fn(f64, f64, f64) -> Tuple,
+) -> Result<Tuple, String> {
+    let mut next = || -> Result<f64, String> {
+        let token = tokens.next().ok_or("Expected 3 coordinates")?;
+        token
+            .parse()
+            .map_err(|_| format!("Invalid number '{token}'"))
+    };
+    Ok(make(next()?, next()?, next()?))
+}
+
+/// Parses a face corner (`v`, `v/vt`, `v//vn` or `v/vt/vn`) into its vertex
+/// and, if present, its normal.
+fn parse_corner(
+    token: &str,
+    vertices: &[Tuple],
+    normals: &[Tuple],
+) -> Result<(Tuple, Option<Tuple>), String> {
+    let mut parts = token.split('/');
+    let vertex = resolve_index(parts.next().unwrap_or_default(), vertices, "vertex")?;
+    let normal = match parts.nth(1) {
+        Some(n) if !n.is_empty() => Some(resolve_index(n, normals, "normal")?),
+        _ => None,
+    };
+    Ok((vertex, normal))
+}
+
+/// Looks up a 1-based index; negative indices count back from the end.
+fn resolve_index(token: &str, items: &[Tuple], kind: &str) -> Result<Tuple, String> {
+    let index: isize = token
+        .parse()
+        .map_err(|_| format!("Invalid {kind} index '{token}'"))?;
+    let resolved = match index {
+        i if i > 0 => Some(i as usize - 1),
+        i if i < 0 => items.len().checked_sub(i.unsigned_abs()),
+        _ => None,
+    };
+    resolved
+        .and_then(|i| items.get(i).copied())
+        .ok_or_else(|| format!("{kind} index {index} is out of range"))
+}
+
+fn fan_triangulation(corners: &[(Tuple, Option<Tuple>)]) -> Vec<Box<dyn Shape>> {
+    let (p1, n1) = corners[0];
+    corners[1..]
+        .windows(2)
+        .map(|w| -> Box<dyn Shape> {
+            let ((p2, n2), (p3, n3)) = (w[0], w[1]);
+            match (n1, n2, n3) {
+                (Some(n1), Some(n2), Some(n3)) => {
+                    Box::new(SmoothTriangle::new(p1, p2, p3, n1, n2, n3))
+                }
+                _ => Box::new(Triangle::new(p1, p2, p3)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::Ray,
+        shapes::{Shape, SmoothTriangle, Triangle},
+        tuples::helpers::{point, vector},
+        world::World,
+    };
+
+    use super::{parse_obj, IgnoredLine, ObjError, ObjFile};
+
+    /// Shapes are trait objects, so compare them through their debug output.
+    fn assert_shape(actual: &dyn Shape, expected: &dyn Shape) {
+        assert_eq!(format!("{actual:?}"), format!("{expected:?}"));
+    }
+
+    fn tri(obj: &ObjFile, a: usize, b: usize, c: usize) -> Triangle {
+        Triangle::new(
+            obj.vertex(a).unwrap(),
+            obj.vertex(b).unwrap(),
+            obj.vertex(c).unwrap(),
+        )
+    }
+
+    #[test]
+    fn ignoring_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright\n\
+                         who traveled much faster than light.\n\
+                         \n\
+                         # She set out one day\n\
+                         in a relative way,\n\
+                         and came back the previous night.";
+        let obj = parse_obj(gibberish).unwrap();
+        assert_eq!(obj.ignored().len(), 4);
+        assert_eq!(
+            obj.ignored()[2],
+            IgnoredLine {
+                line: 5,
+                text: "in a relative way,".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn vertex_records() {
+        let file = "v -1 1 0\nv -1.0000 0.5000 0.0000\nv 1 0 0\nv 1 1 0";
+        let obj = parse_obj(file).unwrap();
+        assert_eq!(obj.vertex(1), Some(point(-1, 1, 0)));
+        assert_eq!(obj.vertex(2), Some(point(-1, 0.5, 0)));
+        assert_eq!(obj.vertex(3), Some(point(1, 0, 0)));
+        assert_eq!(obj.vertex(4), Some(point(1, 1, 0)));
+        assert_eq!(obj.vertex(0), None);
+    }
+
+    #[test]
+    fn parsing_triangle_faces() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\nf 1 2 3\nf 1 3 4";
+        let obj = parse_obj(file).unwrap();
+        let g = obj.default_group();
+        assert_eq!(g.len(), 2);
+        assert_shape(&*g[0], &tri(&obj, 1, 2, 3));
+        assert_shape(&*g[1], &tri(&obj, 1, 3, 4));
+    }
+
+    #[test]
+    fn triangulating_polygons() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\nv 0 2 0\n\nf 1 2 3 4 5";
+        let obj = parse_obj(file).unwrap();
+        let g = obj.default_group();
+        assert_eq!(g.len(), 3);
+        assert_shape(&*g[0], &tri(&obj, 1, 2, 3));
+        assert_shape(&*g[1], &tri(&obj, 1, 3, 4));
+        assert_shape(&*g[2], &tri(&obj, 1, 4, 5));
+    }
+
+    #[test]
+    fn triangles_in_groups() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\
+                    g FirstGroup\nf 1 2 3\ng SecondGroup\nf 1 3 4";
+        let obj = parse_obj(file).unwrap();
+        assert!(obj.default_group().is_empty());
+        assert_eq!(
+            obj.group_names().collect::<Vec<_>>(),
+            ["FirstGroup", "SecondGroup"]
+        );
+        assert_shape(&*obj.group("FirstGroup").unwrap()[0], &tri(&obj, 1, 2, 3));
+        assert_shape(&*obj.group("SecondGroup").unwrap()[0], &tri(&obj, 1, 3, 4));
+        assert!(obj.group("Missing").is_none());
+    }
+
+    #[test]
+    fn vertex_normal_records() {
+        let file = "vn 0 0 1\nvn 0.707 0 -0.707\nvn 1 2 3";
+        let obj = parse_obj(file).unwrap();
+        assert_eq!(obj.normal(1), Some(vector(0, 0, 1)));
+        assert_eq!(obj.normal(2), Some(vector(0.707, 0, -0.707)));
+        assert_eq!(obj.normal(3), Some(vector(1, 2, 3)));
+    }
+
+    #[test]
+    fn faces_with_normals() {
+        let file = "v 0 1 0\nv -1 0 0\nv 1 0 0\n\
+                    vn -1 0 0\nvn 1 0 0\nvn 0 1 0\n\
+                    f 1//3 2//1 3//2\nf 1/0/3 2/102/1 3/14/2";
+        let obj = parse_obj(file).unwrap();
+        let expected = SmoothTriangle::new(
+            obj.vertex(1).unwrap(),
+            obj.vertex(2).unwrap(),
+            obj.vertex(3).unwrap(),
+            obj.normal(3).unwrap(),
+            obj.normal(1).unwrap(),
+            obj.normal(2).unwrap(),
+        );
+        let g = obj.default_group();
+        assert_eq!(g.len(), 2);
+        assert_shape(&*g[0], &expected);
+        assert_shape(&*g[1], &expected);
+    }
+
+    #[test]
+    fn negative_indices() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nf -3 -2 -1";
+        let obj = parse_obj(file).unwrap();
+        assert_shape(&*obj.default_group()[0], &tri(&obj, 1, 2, 3));
+    }
+
+    #[test]
+    fn errors_report_line() {
+        let cases = [
+            ("v 1 2 3\nv 1 two 3", 2),
+            ("v 1 2\n", 1),
+            ("v 1 2 3\nv 1 2 3\n\nf 1 2 5", 4),
+            ("v 1 2 3\nv 1 2 3\nv 1 2 3\nf 1 2", 4),
+            ("v 1 2 3\nv 1 2 3\nv 1 2 3\nf 1//1 2 3", 4),
+            ("v 1 2 3\nf 0 1 1", 2),
+        ];
+        for (file, expected) in cases {
+            match parse_obj(file) {
+                Err(ObjError::Parse { line, .. }) => assert_eq!(line, expected, "{file}"),
+                other => panic!("Expected a parse error for {file:?}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn mesh_inserted_into_world() {
+        let file = "v -1 1 0\nv -1 -1 0\nv 1 -1 0\nv 1 1 0\ng Quad\nf 1 2 3 4";
+        let mesh = parse_obj(file).unwrap().into_mesh();
+        assert_eq!(mesh.len(), 2);
+
+        let mut w = World::default();
+        w.objetcs_mut().clear();
+        w.objetcs_mut().push(Box::new(mesh));
+        let xs = w.intersect(&Ray::new(point(0.5, 0.5, -5), vector(0, 0, 1)));
+        assert_eq!(xs.len(), 1);
+    }
+
+    #[test]
+    fn group_keeps_obj_groups() {
+        let file = "v -1 1 0\nv -1 -1 0\nv 1 -1 0\nv 1 1 0\n\
+                    f 1 2 3\ng First\nf 1 3 4\ng Empty\ng Second\nf 1 2 4";
+        let g = parse_obj(file).unwrap().into_group();
+        assert_eq!(g.len(), 3);
+    }
+}
@@ -3,7 +3,7 @@ mod gradient;
 mod ring;
 mod solid;
 mod stripe;
-use std::fmt::Debug;
+use std::{borrow::Cow, fmt::Debug};
 
 pub use checker::CheckerPattern;
 pub use gradient::GradientPattern;
@@ -11,30 +11,44 @@ pub use ring::RingPattern;
 pub use solid::Solid;
 pub use stripe::StripePattern;
 
-use crate::{
-    matrix::helpers::Mat4,
-    shapes::Shape,
-    tuples::{helpers::color, Tuple},
-};
+use crate::{matrix::helpers::Mat4, shapes::Shape, tuples::Tuple};
 
-pub trait Pattern: Debug {
+#[cfg(test)]
+use crate::tuples::helpers::color;
+
+#[cfg_attr(feature = "serde", typetag::serde(tag = "type"))]
+pub trait Pattern: Debug + Send + Sync {
     fn at(&self, point: &Tuple) -> Tuple;
     fn transform(&self) -> &Mat4;
     fn set_transform(&mut self, new_transform: Mat4);
 
+    /// The inverse of `transform()`. Patterns that cache it should override this.
+    fn inverse(&self) -> Cow<'_, Mat4> {
+        Cow::Owned(self.transform().inverse())
+    }
+
     fn at_object(&self, object: &dyn Shape, point: &Tuple) -> Tuple {
-        let object_point = object.transform().inverse() * *point;
-        let pattern_point = self.transform().inverse() * object_point;
+        self.at_object_at_time(object, point, 0.0)
+    }
+
+    /// The color at `point` on `object`, as the object is placed at `time`.
+    fn at_object_at_time(&self, object: &dyn Shape, point: &Tuple, time: f64) -> Tuple {
+        let object_point = object.world_to_object_at(*point, time);
+        let pattern_point = &*self.inverse() * object_point;
 
         self.at(&pattern_point)
     }
 }
 
+#[cfg(test)]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TestPattern {
     m: Mat4,
 }
 
+#[cfg(test)]
+#[allow(clippy::new_without_default)]
 impl TestPattern {
     pub fn new() -> Self {
         Self {
@@ -43,6 +57,8 @@ impl TestPattern {
     }
 }
 
+#[cfg(test)]
+#[cfg_attr(feature = "serde", typetag::serde(name = "test-pattern"))]
 impl Pattern for TestPattern {
     fn at(&self, point: &Tuple) -> Tuple {
         color(point.x(), point.y(), point.z())
@@ -1,22 +1,26 @@
+use std::borrow::Cow;
+
 use crate::{
     matrix::helpers::Mat4,
+    transformation::Transform,
     tuples::{FEquals, Tuple},
 };
 
 use super::Pattern;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CheckerPattern {
     a: Tuple,
     b: Tuple,
-    transform: Mat4,
+    transform: Transform,
 }
 impl CheckerPattern {
     pub fn new(a: Tuple, b: Tuple) -> Self {
         CheckerPattern {
             a,
             b,
-            transform: Mat4::identity(),
+            transform: Transform::identity(),
         }
     }
     pub fn a(&self) -> Tuple {
@@ -27,6 +31,7 @@ impl CheckerPattern {
     }
 }
 
+#[cfg_attr(feature = "serde", typetag::serde(name = "checkers"))]
 impl Pattern for CheckerPattern {
     fn at(&self, point: &Tuple) -> Tuple {
         if ((point.x().floor() + point.y().floor() + point.z().floor()) % 2.0).eps_eq(0.0) {
@@ -36,10 +41,13 @@ impl Pattern for CheckerPattern {
         }
     }
     fn transform(&self) -> &Mat4 {
-        &self.transform
+        self.transform.matrix()
     }
     fn set_transform(&mut self, new_transform: Mat4) {
-        self.transform = new_transform;
+        self.transform = Transform::new(new_transform);
+    }
+    fn inverse(&self) -> Cow<'_, Mat4> {
+        Cow::Borrowed(self.transform.inverse())
     }
 }
 
@@ -47,7 +55,7 @@ impl Pattern for CheckerPattern {
 mod tests {
     use crate::{
         patterns::Pattern,
-        tuples::helpers::{color, colors, point},
+        tuples::helpers::{colors, point},
     };
 
     use super::CheckerPattern;
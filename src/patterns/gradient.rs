@@ -1,19 +1,22 @@
-use crate::{matrix::helpers::Mat4, tuples::Tuple};
+use std::borrow::Cow;
+
+use crate::{matrix::helpers::Mat4, transformation::Transform, tuples::Tuple};
 
 use super::Pattern;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientPattern {
     a: Tuple,
     b: Tuple,
-    transform: Mat4,
+    transform: Transform,
 }
 impl GradientPattern {
     pub fn new(a: Tuple, b: Tuple) -> Self {
         GradientPattern {
             a,
             b,
-            transform: Mat4::identity(),
+            transform: Transform::identity(),
         }
     }
     pub fn a(&self) -> Tuple {
@@ -24,6 +27,7 @@ impl GradientPattern {
     }
 }
 
+#[cfg_attr(feature = "serde", typetag::serde(name = "gradient"))]
 impl Pattern for GradientPattern {
     fn at(&self, point: &Tuple) -> Tuple {
         let distance = self.a - self.b;
@@ -32,10 +36,13 @@ impl Pattern for GradientPattern {
         self.a - distance * fraction
     }
     fn transform(&self) -> &Mat4 {
-        &self.transform
+        self.transform.matrix()
     }
     fn set_transform(&mut self, new_transform: Mat4) {
-        self.transform = new_transform;
+        self.transform = Transform::new(new_transform);
+    }
+    fn inverse(&self) -> Cow<'_, Mat4> {
+        Cow::Borrowed(self.transform.inverse())
     }
 }
 
@@ -1,22 +1,26 @@
+use std::borrow::Cow;
+
 use crate::{
     matrix::helpers::Mat4,
+    transformation::Transform,
     tuples::{FEquals, Tuple},
 };
 
 use super::Pattern;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RingPattern {
     a: Tuple,
     b: Tuple,
-    transform: Mat4,
+    transform: Transform,
 }
 impl RingPattern {
     pub fn new(a: Tuple, b: Tuple) -> Self {
         RingPattern {
             a,
             b,
-            transform: Mat4::identity(),
+            transform: Transform::identity(),
         }
     }
     pub fn a(&self) -> Tuple {
@@ -27,6 +31,7 @@ impl RingPattern {
     }
 }
 
+#[cfg_attr(feature = "serde", typetag::serde(name = "rings"))]
 impl Pattern for RingPattern {
     fn at(&self, point: &Tuple) -> Tuple {
         if ((point.x() * point.x() + point.z() * point.z()).sqrt() % 2.0).eps_eq(0.0) {
@@ -36,10 +41,13 @@ impl Pattern for RingPattern {
         }
     }
     fn transform(&self) -> &Mat4 {
-        &self.transform
+        self.transform.matrix()
     }
     fn set_transform(&mut self, new_transform: Mat4) {
-        self.transform = new_transform;
+        self.transform = Transform::new(new_transform);
+    }
+    fn inverse(&self) -> Cow<'_, Mat4> {
+        Cow::Borrowed(self.transform.inverse())
     }
 }
 
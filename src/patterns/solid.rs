@@ -1,9 +1,13 @@
+use std::borrow::Cow;
+
 use crate::{matrix::helpers::Mat4, tuples::Tuple};
 
 use super::Pattern;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Solid {
+    #[cfg_attr(feature = "serde", serde(skip, default = "Mat4::identity"))]
     m: Mat4,
     color: Tuple,
 }
@@ -17,6 +21,7 @@ impl Solid {
     }
 }
 
+#[cfg_attr(feature = "serde", typetag::serde(name = "solid"))]
 impl Pattern for Solid {
     fn at(&self, _: &Tuple) -> Tuple {
         self.color
@@ -27,4 +32,8 @@ impl Pattern for Solid {
     }
 
     fn set_transform(&mut self, _: Mat4) {}
+
+    fn inverse(&self) -> Cow<'_, Mat4> {
+        Cow::Borrowed(&self.m)
+    }
 }
@@ -1,16 +1,19 @@
+use std::borrow::Cow;
+
 use crate::{
     matrix::helpers::Mat4,
-    shapes::Shape,
+    transformation::Transform,
     tuples::{FEquals, Tuple},
 };
 
 use super::Pattern;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StripePattern {
     a: Tuple,
     b: Tuple,
-    transform: Mat4,
+    transform: Transform,
 }
 
 impl StripePattern {
@@ -18,7 +21,7 @@ impl StripePattern {
         StripePattern {
             a,
             b,
-            transform: Mat4::identity(),
+            transform: Transform::identity(),
         }
     }
     pub fn a(&self) -> Tuple {
@@ -29,6 +32,7 @@ impl StripePattern {
     }
 }
 
+#[cfg_attr(feature = "serde", typetag::serde(name = "stripes"))]
 impl Pattern for StripePattern {
     fn at(&self, point: &Tuple) -> Tuple {
         if (point.x().floor() % 2.0).eps_eq(0.0) {
@@ -38,10 +42,13 @@ impl Pattern for StripePattern {
         }
     }
     fn transform(&self) -> &Mat4 {
-        &self.transform
+        self.transform.matrix()
     }
     fn set_transform(&mut self, new_transform: Mat4) {
-        self.transform = new_transform;
+        self.transform = Transform::new(new_transform);
+    }
+    fn inverse(&self) -> Cow<'_, Mat4> {
+        Cow::Borrowed(self.transform.inverse())
     }
 }
 
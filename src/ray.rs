@@ -1,6 +1,8 @@
+use std::ptr;
+
 use crate::{
     matrix::helpers::Mat4,
-    shapes::{Shape, Sphere},
+    shapes::Shape,
     tuples::{Tuple, EPSILON},
 };
 
@@ -8,11 +10,20 @@ use crate::{
 pub struct Ray {
     origin: Tuple,
     direction: Tuple,
+    time: f64,
 }
 
 impl Ray {
     pub fn new(origin: Tuple, direction: Tuple) -> Ray {
-        Ray { origin, direction }
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+    /// The same ray, sent at `time` (used for moving shapes).
+    pub fn with_time(self, time: f64) -> Ray {
+        Ray { time, ..self }
     }
     pub fn origin(&self) -> Tuple {
         self.origin
@@ -20,14 +31,14 @@ impl Ray {
     pub fn direction(&self) -> Tuple {
         self.direction
     }
+    pub fn time(&self) -> f64 {
+        self.time
+    }
     pub fn at(&self, t: f64) -> Tuple {
         self.origin + self.direction * t
     }
     pub fn transform(&self, transform: &Mat4) -> Ray {
-        Ray::new(
-            transform.clone() * self.origin(),
-            transform.clone() * self.direction(),
-        )
+        Ray::new(transform * self.origin(), transform * self.direction()).with_time(self.time)
     }
 }
 
@@ -35,55 +46,154 @@ impl Ray {
 pub struct Intersection<'a> {
     object: &'a dyn Shape,
     t: f64,
+    u: f64,
+    v: f64,
+    time: f64,
 }
 
 impl<'a> Intersection<'a> {
     pub fn new(t: f64, object: &'a dyn Shape) -> Self {
-        Intersection { object, t }
+        Intersection::with_uv(t, object, 0.0, 0.0)
+    }
+
+    /// An intersection that also records where on the surface it happened,
+    /// as barycentric `u`/`v` coordinates (used by triangles).
+    pub fn with_uv(t: f64, object: &'a dyn Shape, u: f64, v: f64) -> Self {
+        Intersection {
+            object,
+            t,
+            u,
+            v,
+            time: 0.0,
+        }
+    }
+
+    /// The same intersection, made by a ray sent at `time`.
+    pub fn with_time(self, time: f64) -> Self {
+        Intersection { time, ..self }
     }
 
     pub fn t(&self) -> f64 {
         self.t
     }
 
+    pub fn u(&self) -> f64 {
+        self.u
+    }
+
+    pub fn v(&self) -> f64 {
+        self.v
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
     pub fn object(&self) -> &'a dyn Shape {
         self.object
     }
 
-    pub fn prepare_comps(&self, ray: &Ray) -> Computations {
+    fn is(&self, other: &Intersection) -> bool {
+        self.t == other.t && ptr::addr_eq(self.object, other.object)
+    }
+
+    /// Prepares the shading data assuming this is the only intersection on
+    /// the ray, i.e. without any surrounding refractive media.
+    pub fn prepare_comps(&self, ray: &Ray) -> Computations<'a> {
+        self.prepare_comps_with(ray, std::slice::from_ref(self))
+    }
+
+    /// Prepares the shading data for this hit, using the sorted intersections
+    /// `xs` of the whole ray to find the refractive indices on either side.
+    pub fn prepare_comps_with(&self, ray: &Ray, xs: &[Intersection]) -> Computations<'a> {
         let point = ray.at(self.t);
-        let mut normalv = self.object.normal_at(point);
+        let mut normalv = self.object.normal_at_hit(point, self);
         let inside = normalv.dot(&-ray.direction()) < 0.0;
         normalv *= if inside { -1.0 } else { 1.0 };
 
+        let (mut n1, mut n2) = (1.0, 1.0);
+        let mut containers: Vec<&dyn Shape> = Vec::new();
+        for i in xs {
+            let is_hit = self.is(i);
+            if is_hit {
+                n1 = containers
+                    .last()
+                    .map_or(1.0, |o| o.material().refractive_index);
+            }
+
+            match containers.iter().position(|o| ptr::addr_eq(*o, i.object)) {
+                Some(index) => {
+                    containers.remove(index);
+                }
+                None => containers.push(i.object),
+            }
+
+            if is_hit {
+                n2 = containers
+                    .last()
+                    .map_or(1.0, |o| o.material().refractive_index);
+                break;
+            }
+        }
+
         Computations {
             t: self.t,
+            time: self.time,
             object: self.object,
             point,
             eyev: -ray.direction(),
             normalv,
             inside,
             over_point: point + normalv * EPSILON,
+            under_point: point - normalv * EPSILON,
+            reflectv: ray.direction().reflect(normalv),
+            n1,
+            n2,
         }
     }
 }
 
 pub struct Computations<'a> {
     pub t: f64,
+    /// The time of the ray, for the rays spawned at this hit.
+    pub time: f64,
     pub object: &'a dyn Shape,
     pub point: Tuple,
     pub eyev: Tuple,
     pub normalv: Tuple,
     pub inside: bool,
     pub over_point: Tuple,
+    pub under_point: Tuple,
+    pub reflectv: Tuple,
+    pub n1: f64,
+    pub n2: f64,
+}
+
+impl Computations<'_> {
+    /// Schlick's approximation of the Fresnel reflectance at this hit.
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eyev.dot(&self.normalv);
+
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n * n * (1.0 - cos * cos);
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+            cos = (1.0 - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
 }
 
 pub trait Intersections {
-    fn hit(&self) -> Option<Intersection>;
+    fn hit(&self) -> Option<Intersection<'_>>;
 }
 
 impl Intersections for Vec<Intersection<'_>> {
-    fn hit(&self) -> Option<Intersection> {
+    fn hit(&self) -> Option<Intersection<'_>> {
         self.iter()
             .filter(|f| f.t() >= 0.0)
             .min_by(|a, b| a.t().partial_cmp(&b.t()).unwrap())
@@ -95,7 +205,7 @@ impl Intersections for Vec<Intersection<'_>> {
 mod tests {
     use crate::{
         ray::Intersections,
-        shapes::{Shape, Sphere},
+        shapes::{Plane, Shape, Sphere},
         transformation::{scaling, translation},
         tuples::{
             helpers::{point, vector},
@@ -136,6 +246,16 @@ mod tests {
     }
 
     #[test]
+    fn intersection_encapsulates_uv() {
+        let s = Sphere::new();
+        let i = Intersection::with_uv(3.5, &s, 0.2, 0.4);
+
+        assert!(i.u().eps_eq(0.2));
+        assert!(i.v().eps_eq(0.4));
+    }
+
+    #[test]
+    #[allow(clippy::useless_vec)]
     fn aggregating_intersections() {
         let s = Sphere::new();
         let i1 = Intersection::new(1.0, &s);
@@ -149,6 +269,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn hit_all_positive_t() {
         let s = Sphere::new();
 
@@ -162,6 +283,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn hit_some_negative_t() {
         let s = Sphere::new();
 
@@ -175,6 +297,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn hit_all_negative_t() {
         let s = Sphere::new();
 
@@ -188,6 +311,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn hit_lowest_t() {
         let s = Sphere::new();
 
@@ -235,6 +359,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn hit_on_outside() {
         let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
         let shape = Sphere::new();
@@ -244,6 +369,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn hit_on_inside() {
         let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
         let shape = Sphere::new();
@@ -266,4 +392,115 @@ mod tests {
         assert!(comps.over_point.z() < -EPSILON / 2.0);
         assert!(comps.point.z() > comps.over_point.z());
     }
+
+    #[test]
+    fn precomputing_reflection_vector() {
+        let shape = Plane::new();
+        let r = Ray::new(
+            point(0, 1, -1),
+            vector(0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let i = Intersection::new(2.0_f64.sqrt(), &shape);
+        let comps = i.prepare_comps(&r);
+        assert_eq!(
+            comps.reflectv,
+            vector(0, 2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0)
+        );
+    }
+
+    #[test]
+    fn finding_n1_and_n2() {
+        let mut a = Sphere::glass();
+        a.set_transform(scaling(2.0, 2.0, 2.0));
+        a.material_mut().refractive_index = 1.5;
+        let mut b = Sphere::glass();
+        b.set_transform(translation(0.0, 0.0, -0.25));
+        b.material_mut().refractive_index = 2.0;
+        let mut c = Sphere::glass();
+        c.set_transform(translation(0.0, 0.0, 0.25));
+        c.material_mut().refractive_index = 2.5;
+
+        let r = Ray::new(point(0, 0, -4), vector(0, 0, 1));
+        let xs = vec![
+            Intersection::new(2.0, &a),
+            Intersection::new(2.75, &b),
+            Intersection::new(3.25, &c),
+            Intersection::new(4.75, &b),
+            Intersection::new(5.25, &c),
+            Intersection::new(6.0, &a),
+        ];
+
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+        for (i, (n1, n2)) in expected.into_iter().enumerate() {
+            let comps = xs[i].prepare_comps_with(&r, &xs);
+            assert!(comps.n1.eps_eq(n1));
+            assert!(comps.n2.eps_eq(n2));
+        }
+    }
+
+    #[test]
+    fn under_point_is_below_surface() {
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let mut shape = Sphere::glass();
+        shape.set_transform(translation(0.0, 0.0, 1.0));
+        let i = Intersection::new(5.0, &shape);
+        let xs = vec![i.clone()];
+
+        let comps = i.prepare_comps_with(&r, &xs);
+        assert!(comps.under_point.z() > EPSILON / 2.0);
+        assert!(comps.point.z() < comps.under_point.z());
+    }
+
+    #[test]
+    fn schlick_total_internal_reflection() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0, 0, 2.0_f64.sqrt() / 2.0), vector(0, 1, 0));
+        let xs = vec![
+            Intersection::new(-2.0_f64.sqrt() / 2.0, &shape),
+            Intersection::new(2.0_f64.sqrt() / 2.0, &shape),
+        ];
+
+        let comps = xs[1].prepare_comps_with(&r, &xs);
+        assert!(comps.schlick().eps_eq(1.0));
+    }
+
+    #[test]
+    fn schlick_perpendicular() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0, 0, 0), vector(0, 1, 0));
+        let xs = vec![
+            Intersection::new(-1.0, &shape),
+            Intersection::new(1.0, &shape),
+        ];
+
+        let comps = xs[1].prepare_comps_with(&r, &xs);
+        assert!(comps.schlick().eps_eq(0.04));
+    }
+
+    #[test]
+    fn schlick_small_angle() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0, 0.99, -2), vector(0, 0, 1));
+        let xs = vec![Intersection::new(1.8589, &shape)];
+
+        let comps = xs[0].prepare_comps_with(&r, &xs);
+        assert!((comps.schlick() - 0.48873).abs() < 0.0001);
+    }
+
+    #[test]
+    fn rays_keep_their_time() {
+        let r = Ray::new(point(1, 2, 3), vector(0, 1, 0));
+        assert_eq!(r.time(), 0.0);
+
+        let r = r.with_time(0.5);
+        assert_eq!(r.time(), 0.5);
+        assert_eq!(r.transform(&translation(3.0, 4.0, 5.0)).time(), 0.5);
+    }
 }
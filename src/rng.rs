@@ -0,0 +1,81 @@
+//! A small seedable random number generator (xorshift64*).
+//!
+//! Sampling code derives a fresh generator from the seed and whatever it is
+//! sampling (a pixel, a shading point, ...) instead of sharing one, so the
+//! result doesn't depend on the order threads render in.
+
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    #[cfg(test)]
+    pub fn new(seed: u64) -> Rng {
+        Rng::from_seeds(&[seed])
+    }
+
+    /// A generator seeded by mixing all of `seeds`.
+    pub fn from_seeds(seeds: &[u64]) -> Rng {
+        let state = seeds
+            .iter()
+            .fold(0x853c_49e6_748f_ea9b, |acc, &s| splitmix64(acc ^ s));
+        Rng {
+            // xorshift gets stuck on a zero state
+            state: if state == 0 {
+                0x9e37_79b9_7f4a_7c15
+            } else {
+                state
+            },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+        assert_ne!(
+            Rng::from_seeds(&[1, 2]).next_u64(),
+            Rng::from_seeds(&[2, 1]).next_u64()
+        );
+    }
+
+    #[test]
+    fn floats_in_unit_range() {
+        let mut r = Rng::new(7);
+        let values: Vec<_> = (0..1000).map(|_| r.next_f64()).collect();
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!((mean - 0.5).abs() < 0.05);
+    }
+}
@@ -0,0 +1,854 @@
+//! A loader for YAML scene descriptions, in the format of the book's bonus
+//! chapters. A scene is a list of items that either `add` something (the
+//! camera, a light or a shape) or `define` a material or transform list that
+//! later items refer to by name:
+//!
+//! ```yaml
+//! - add: camera
+//!   width: 100
+//!   height: 50
+//!   field-of-view: 1.047
+//!   from: [0, 1.5, -5]
+//!   to: [0, 1, 0]
+//!   up: [0, 1, 0]
+//! - add: light
+//!   at: [-10, 10, -10]
+//!   intensity: [1, 1, 1]
+//! - define: shiny
+//!   value:
+//!     reflective: 0.3
+//! - define: red-shiny
+//!   extend: shiny
+//!   value:
+//!     color: [1, 0, 0]
+//! - add: sphere
+//!   material: red-shiny
+//!   transform:
+//!     - [scale, 0.5, 0.5, 0.5]
+//!     - [translate, 0, 0.5, 0]
+//! ```
+//!
+//! Transforms are applied in the order they are listed. Unknown keys are
+//! reported as errors rather than ignored, so typos don't go unnoticed.
+
+mod node;
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    camera::Camera,
+    light::{AreaLight, Attenuation, DirectionalLight, PointLight, SpotLight},
+    material::Material,
+    matrix::helpers::Mat4,
+    obj::load_obj,
+    patterns::{CheckerPattern, GradientPattern, Pattern, RingPattern, StripePattern},
+    shapes::{Cone, Csg, CsgOperation, Cube, Cylinder, Group, Plane, Shape, Sphere},
+    transformation::{
+        rotation_x, rotation_y, rotation_z, scaling, shearing, translation, view_transform,
+    },
+    tuples::{
+        helpers::{color, point, vector},
+        Tuple,
+    },
+    world::World,
+};
+
+use node::{Entry, Node, Value};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    /// The file is not valid YAML.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The YAML doesn't describe a valid scene; `key` is the offending key.
+    Invalid {
+        line: usize,
+        key: String,
+        message: String,
+    },
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "Failed to read scene file: {e}"),
+            SceneError::Syntax { line, message } => write!(f, "Line {line}: {message}"),
+            SceneError::Invalid { line, key, message } => {
+                write!(f, "Line {line}, `{key}`: {message}")
+            }
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(value: io::Error) -> Self {
+        SceneError::Io(value)
+    }
+}
+
+/// A loaded scene, ready to render with `camera.render(&world)`.
+pub struct Scene {
+    pub world: World,
+    pub camera: Camera,
+}
+
+/// Loads a scene file. OBJ files it refers to are looked up relative to it.
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)?;
+    let base = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+    Loader::new(base).load(&source)
+}
+
+/// Parses a scene. OBJ files it refers to are looked up relative to the
+/// working directory.
+pub fn parse_scene(source: &str) -> Result<Scene, SceneError> {
+    Loader::new(PathBuf::new()).load(source)
+}
+
+fn invalid(line: usize, key: &str, message: impl Into<String>) -> SceneError {
+    SceneError::Invalid {
+        line,
+        key: key.to_string(),
+        message: message.into(),
+    }
+}
+
+/// The entries of a mapping, keeping track of which were used so unknown
+/// keys can be reported.
+struct Fields<'a> {
+    line: usize,
+    entries: &'a [Entry],
+    used: Vec<bool>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(node: &'a Node, key: &str) -> Result<Fields<'a>, SceneError> {
+        let entries = node
+            .as_map()
+            .ok_or_else(|| invalid(node.line, key, "Expected a mapping"))?;
+        Ok(Fields {
+            line: node.line,
+            entries,
+            used: vec![false; entries.len()],
+        })
+    }
+
+    fn get(&mut self, key: &str) -> Option<&'a Entry> {
+        let i = self.entries.iter().position(|e| e.key == key)?;
+        self.used[i] = true;
+        Some(&self.entries[i])
+    }
+
+    fn require(&mut self, key: &str) -> Result<&'a Entry, SceneError> {
+        self.get(key)
+            .ok_or_else(|| invalid(self.line, key, "Missing required key"))
+    }
+
+    fn finish(self) -> Result<(), SceneError> {
+        match self
+            .entries
+            .iter()
+            .zip(&self.used)
+            .find(|(_, used)| !**used)
+        {
+            Some((e, _)) => Err(invalid(e.line, &e.key, "Unknown key")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Entry {
+    fn error(&self, message: impl Into<String>) -> SceneError {
+        invalid(self.value.line, &self.key, message)
+    }
+
+    fn string(&self) -> Result<&str, SceneError> {
+        self.value
+            .as_scalar()
+            .ok_or_else(|| self.error("Expected a single value"))
+    }
+
+    fn number(&self) -> Result<f64, SceneError> {
+        number(&self.value, &self.key)
+    }
+
+    fn count(&self) -> Result<usize, SceneError> {
+        self.string()?
+            .parse()
+            .map_err(|_| self.error("Expected a whole number"))
+    }
+
+    fn boolean(&self) -> Result<bool, SceneError> {
+        match self.string()? {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(self.error("Expected `true` or `false`")),
+        }
+    }
+
+    fn triple(&self) -> Result<[f64; 3], SceneError> {
+        triple(&self.value, &self.key)
+    }
+
+    fn point(&self) -> Result<Tuple, SceneError> {
+        self.triple().map(|[x, y, z]| point(x, y, z))
+    }
+
+    fn vector(&self) -> Result<Tuple, SceneError> {
+        self.triple().map(|[x, y, z]| vector(x, y, z))
+    }
+
+    fn color(&self) -> Result<Tuple, SceneError> {
+        self.triple().map(|[r, g, b]| color(r, g, b))
+    }
+}
+
+fn number(node: &Node, key: &str) -> Result<f64, SceneError> {
+    node.as_scalar()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid(node.line, key, "Expected a number"))
+}
+
+fn triple(node: &Node, key: &str) -> Result<[f64; 3], SceneError> {
+    match node.as_seq() {
+        Some([x, y, z]) => Ok([number(x, key)?, number(y, key)?, number(z, key)?]),
+        _ => Err(invalid(node.line, key, "Expected a list of 3 numbers")),
+    }
+}
+
+struct Loader {
+    base: PathBuf,
+    defines: HashMap<String, Node>,
+}
+
+impl Loader {
+    fn new(base: PathBuf) -> Loader {
+        Loader {
+            base,
+            defines: HashMap::new(),
+        }
+    }
+
+    fn load(mut self, source: &str) -> Result<Scene, SceneError> {
+        let root = node::parse(source)?;
+        let items = root
+            .as_seq()
+            .ok_or_else(|| invalid(root.line, "scene", "Expected a list of items"))?;
+
+        let mut world = World::new();
+        let mut camera = None;
+        for item in items {
+            let mut fields = Fields::new(item, "scene")?;
+            if let Some(define) = fields.get("define") {
+                self.define(define, &mut fields)?;
+            } else if let Some(add) = fields.get("add") {
+                match add.string()? {
+                    "camera" => {
+                        if camera.is_some() {
+                            return Err(add.error("The scene already has a camera"));
+                        }
+                        camera = Some(self.camera(&mut fields)?);
+                    }
+                    "light" => self.light(&mut world, &mut fields)?,
+                    _ => {
+                        let shape = self.shape(add, &mut fields)?;
+                        world.objetcs_mut().push(shape);
+                    }
+                }
+            } else {
+                return Err(invalid(item.line, "add", "Expected `add` or `define`"));
+            }
+            fields.finish()?;
+        }
+
+        let camera =
+            camera.ok_or_else(|| invalid(root.line, "camera", "The scene has no camera"))?;
+        Ok(Scene { world, camera })
+    }
+
+    fn define(&mut self, name: &Entry, fields: &mut Fields) -> Result<(), SceneError> {
+        let value = fields.require("value")?;
+        let mut node = self.expand(&value.value, &value.key)?;
+
+        if let Some(extend) = fields.get("extend") {
+            let base = self.lookup(extend)?;
+            node.value = match (&base.value, node.value) {
+                (Value::Map(base), Value::Map(entries)) => {
+                    let mut merged: Vec<Entry> = base
+                        .iter()
+                        .filter(|b| entries.iter().all(|e| e.key != b.key))
+                        .cloned()
+                        .collect();
+                    merged.extend(entries);
+                    Value::Map(merged)
+                }
+                (Value::Seq(base), Value::Seq(items)) => {
+                    Value::Seq(base.iter().cloned().chain(items).collect())
+                }
+                _ => return Err(extend.error("Can only extend a definition of the same kind")),
+            };
+        }
+
+        self.defines.insert(name.string()?.to_string(), node);
+        Ok(())
+    }
+
+    fn lookup(&self, entry: &Entry) -> Result<&Node, SceneError> {
+        let name = entry.string()?;
+        self.defines
+            .get(name)
+            .ok_or_else(|| entry.error(format!("Unknown definition `{name}`")))
+    }
+
+    /// Replaces names in a list by the items of the list they define, so
+    /// transforms can be built from other transforms.
+    fn expand(&self, node: &Node, key: &str) -> Result<Node, SceneError> {
+        let Some(items) = node.as_seq() else {
+            return Ok(node.clone());
+        };
+        let mut expanded = Vec::new();
+        for item in items {
+            match item.as_scalar() {
+                Some(name) => match self.defines.get(name).and_then(Node::as_seq) {
+                    Some(defined) => expanded.extend(defined.iter().cloned()),
+                    None => {
+                        return Err(invalid(
+                            item.line,
+                            key,
+                            format!("Unknown transform definition `{name}`"),
+                        ))
+                    }
+                },
+                None => expanded.push(item.clone()),
+            }
+        }
+        Ok(Node {
+            line: node.line,
+            value: Value::Seq(expanded),
+        })
+    }
+
+    fn camera(&self, fields: &mut Fields) -> Result<Camera, SceneError> {
+        let width = fields.require("width")?.count()?;
+        let height = fields.require("height")?.count()?;
+        let fov = fields.require("field-of-view")?.number()?;
+        let from = fields.require("from")?.point()?;
+        let to = fields.require("to")?.point()?;
+        let up = match fields.get("up") {
+            Some(up) => up.vector()?,
+            None => vector(0, 1, 0),
+        };
+        Ok(Camera::new_transformed(
+            width,
+            height,
+            fov,
+            view_transform(from, to, up),
+        ))
+    }
+
+    fn light(&self, world: &mut World, fields: &mut Fields) -> Result<(), SceneError> {
+        let kind = match fields.get("type") {
+            Some(kind) => kind.string()?,
+            None if fields.entries.iter().any(|e| e.key == "corner") => "area",
+            None => "point",
+        };
+        let intensity = fields.require("intensity")?.color()?;
+        // Directional lights don't fade, so `attenuation` is left unread and
+        // reported as an unknown key.
+        let attenuation = match kind {
+            "directional" => Attenuation::None,
+            _ => match fields.get("attenuation") {
+                Some(a) => attenuation(a)?,
+                None => Attenuation::None,
+            },
+        };
+
+        match kind {
+            "point" => {
+                let at = fields.require("at")?.point()?;
+                world.add_light(PointLight::new(intensity, at).with_attenuation(attenuation));
+            }
+            "area" => {
+                let mut light = AreaLight::new(
+                    intensity,
+                    fields.require("corner")?.point()?,
+                    fields.require("uvec")?.vector()?,
+                    fields.require("usteps")?.count()?,
+                    fields.require("vvec")?.vector()?,
+                    fields.require("vsteps")?.count()?,
+                )
+                .with_attenuation(attenuation);
+                if let Some(jitter) = fields.get("jitter") {
+                    match jitter.boolean() {
+                        Ok(true) => light = light.jittered(0),
+                        Ok(false) => {}
+                        Err(_) => light = light.jittered(jitter.count()? as u64),
+                    }
+                }
+                world.add_light(light);
+            }
+            "spot" => {
+                let light = SpotLight::new(
+                    intensity,
+                    fields.require("at")?.point()?,
+                    fields.require("direction")?.vector()?,
+                    fields.require("inner-angle")?.number()?,
+                    fields.require("outer-angle")?.number()?,
+                );
+                world.add_light(light.with_attenuation(attenuation));
+            }
+            "directional" => {
+                let direction = fields.require("direction")?.vector()?;
+                world.add_light(DirectionalLight::new(intensity, direction));
+            }
+            other => {
+                let entry = fields.require("type")?;
+                return Err(entry.error(format!("Unknown light type `{other}`")));
+            }
+        }
+        Ok(())
+    }
+
+    fn shape(&self, add: &Entry, fields: &mut Fields) -> Result<Box<dyn Shape>, SceneError> {
+        let mut shape: Box<dyn Shape> = match add.string()? {
+            "sphere" => Box::new(Sphere::new()),
+            "plane" => Box::new(Plane::new()),
+            "cube" => Box::new(Cube::new()),
+            kind @ ("cylinder" | "cone") => {
+                let min = match fields.get("min") {
+                    Some(min) => min.number()?,
+                    None => f64::NEG_INFINITY,
+                };
+                let max = match fields.get("max") {
+                    Some(max) => max.number()?,
+                    None => f64::INFINITY,
+                };
+                let closed = match fields.get("closed") {
+                    Some(closed) => closed.boolean()?,
+                    None => false,
+                };
+                if kind == "cylinder" {
+                    Box::new(Cylinder::truncated(min, max, closed))
+                } else {
+                    Box::new(Cone::truncated(min, max, closed))
+                }
+            }
+            "group" => {
+                let children = fields.require("children")?;
+                let items = children
+                    .value
+                    .as_seq()
+                    .ok_or_else(|| children.error("Expected a list of shapes"))?;
+                let mut group = Group::new();
+                for item in items {
+                    group.add_child(self.child(item, &children.key)?);
+                }
+                Box::new(group)
+            }
+            "csg" => {
+                let operation = fields.require("operation")?;
+                let operation = match operation.string()? {
+                    "union" => CsgOperation::Union,
+                    "intersection" => CsgOperation::Intersection,
+                    "difference" => CsgOperation::Difference,
+                    other => return Err(operation.error(format!("Unknown operation `{other}`"))),
+                };
+                let left = fields.require("left")?;
+                let right = fields.require("right")?;
+                Box::new(Csg::new(
+                    operation,
+                    self.child(&left.value, &left.key)?,
+                    self.child(&right.value, &right.key)?,
+                ))
+            }
+            "obj" => {
+                let file = fields.require("file")?;
+                let obj = load_obj(self.base.join(file.string()?))
+                    .map_err(|e| file.error(e.to_string()))?;
+                Box::new(obj.into_group())
+            }
+            other => return Err(add.error(format!("Unknown shape `{other}`"))),
+        };
+
+        if let Some(transform) = fields.get("transform") {
+            shape.set_transform(self.transform(transform)?);
+        }
+        if let Some(material) = fields.get("material") {
+            shape.set_material(self.material(material)?);
+        }
+        Ok(shape)
+    }
+
+    /// A shape nested in a group or CSG shape, given as `add: <shape>`.
+    fn child(&self, node: &Node, key: &str) -> Result<Box<dyn Shape>, SceneError> {
+        let mut fields = Fields::new(node, key)?;
+        let add = fields.require("add")?;
+        let shape = self.shape(add, &mut fields)?;
+        fields.finish()?;
+        Ok(shape)
+    }
+
+    fn transform(&self, entry: &Entry) -> Result<Mat4, SceneError> {
+        let node = match entry.value.as_scalar() {
+            Some(_) => self.lookup(entry)?.clone(),
+            None => self.expand(&entry.value, &entry.key)?,
+        };
+        let items = node
+            .as_seq()
+            .ok_or_else(|| entry.error("Expected a list of transforms"))?;
+
+        let mut transform = Mat4::identity();
+        for item in items {
+            let error = |message: &str| invalid(item.line, &entry.key, message);
+            let parts = item
+                .as_seq()
+                .ok_or_else(|| error("Expected a list like `[translate, x, y, z]`"))?;
+            let Some((op, args)) = parts.split_first() else {
+                return Err(error("Empty transform"));
+            };
+            let args = args
+                .iter()
+                .map(|a| number(a, &entry.key))
+                .collect::<Result<Vec<_>, SceneError>>()?;
+
+            let m = match (op.as_scalar().unwrap_or_default(), args.as_slice()) {
+                ("translate", &[x, y, z]) => translation(x, y, z),
+                ("scale", &[x, y, z]) => scaling(x, y, z),
+                ("rotate-x", &[r]) => rotation_x(r),
+                ("rotate-y", &[r]) => rotation_y(r),
+                ("rotate-z", &[r]) => rotation_z(r),
+                ("shear", &[xy, xz, yx, yz, zx, zy]) => shearing(xy, xz, yx, yz, zx, zy),
+                ("translate" | "scale" | "rotate-x" | "rotate-y" | "rotate-z" | "shear", _) => {
+                    return Err(error("Wrong number of arguments"))
+                }
+                _ => return Err(error("Unknown transform")),
+            };
+            transform = m * transform;
+            if !transform.invertible() {
+                return Err(error("Transform is singular, it flattens the shape"));
+            }
+        }
+        Ok(transform)
+    }
+
+    fn material(&self, entry: &Entry) -> Result<Material, SceneError> {
+        let node = match entry.value.as_scalar() {
+            Some(_) => self.lookup(entry)?,
+            None => &entry.value,
+        };
+
+        let mut fields = Fields::new(node, &entry.key)?;
+        let mut m = Material::default();
+        if let Some(e) = fields.get("color") {
+            m.color = e.color()?.solid();
+        }
+        if let Some(e) = fields.get("pattern") {
+            m.color = self.pattern(e)?;
+        }
+        let numbers: [(&str, &mut f64); 7] = [
+            ("ambient", &mut m.ambient),
+            ("diffuse", &mut m.diffuse),
+            ("specular", &mut m.specular),
+            ("shininess", &mut m.shininess),
+            ("reflective", &mut m.reflective),
+            ("transparency", &mut m.transparency),
+            ("refractive-index", &mut m.refractive_index),
+        ];
+        for (key, value) in numbers {
+            if let Some(e) = fields.get(key) {
+                *value = e.number()?;
+            }
+        }
+        fields.finish()?;
+        Ok(m)
+    }
+
+    fn pattern(&self, entry: &Entry) -> Result<Arc<dyn Pattern>, SceneError> {
+        let mut fields = Fields::new(&entry.value, &entry.key)?;
+        let colors = fields.require("colors")?;
+        let (a, b) = match colors.value.as_seq() {
+            Some([a, b]) => (triple(a, &colors.key)?, triple(b, &colors.key)?),
+            _ => return Err(colors.error("Expected a list of 2 colors")),
+        };
+        let (a, b) = (color(a[0], a[1], a[2]), color(b[0], b[1], b[2]));
+
+        let kind = fields.require("type")?;
+        let mut pattern: Box<dyn Pattern> = match kind.string()? {
+            "stripes" => Box::new(StripePattern::new(a, b)),
+            "checkers" => Box::new(CheckerPattern::new(a, b)),
+            "gradient" => Box::new(GradientPattern::new(a, b)),
+            "rings" => Box::new(RingPattern::new(a, b)),
+            other => return Err(kind.error(format!("Unknown pattern `{other}`"))),
+        };
+        if let Some(transform) = fields.get("transform") {
+            pattern.set_transform(self.transform(transform)?);
+        }
+        fields.finish()?;
+        Ok(Arc::from(pattern))
+    }
+}
+
+fn attenuation(entry: &Entry) -> Result<Attenuation, SceneError> {
+    if let Some(name) = entry.value.as_scalar() {
+        return match name {
+            "none" => Ok(Attenuation::None),
+            "linear" => Ok(Attenuation::Linear),
+            "inverse-square" => Ok(Attenuation::InverseSquare),
+            _ => Err(entry.error(format!("Unknown attenuation `{name}`"))),
+        };
+    }
+    let [constant, linear, quadratic] = entry.triple()?;
+    Attenuation::coefficients(constant, linear, quadratic)
+        .ok_or_else(|| entry.error("Coefficients can't be negative or all zero"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        transformation::{scaling, translation},
+        tuples::{
+            helpers::{color, point},
+            FEquals,
+        },
+    };
+
+    use super::{load_scene, parse_scene, SceneError};
+
+    const SCENE: &str = "
+- add: camera
+  width: 40
+  height: 20
+  field-of-view: 0.785
+  from: [0, 1.5, -5]
+  to: [0, 1, 0]
+  up: [0, 1, 0]
+
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 1, 1]
+
+- define: white
+  value:
+    color: [1, 1, 1]
+    diffuse: 0.7
+    ambient: 0.1
+
+- define: blue
+  extend: white
+  value:
+    color: [0.5, 0.6, 0.9]
+    reflective: 0.2
+
+- define: small
+  value:
+    - [scale, 0.5, 0.5, 0.5]
+
+- add: sphere
+  material: blue
+  transform:
+    - small
+    - [translate, 1, 2, 3]
+
+- add: plane
+  material:
+    pattern:
+      type: checkers
+      colors:
+        - [1, 1, 1]
+        - [0, 0, 0]
+";
+
+    fn error(source: &str) -> (usize, String) {
+        match parse_scene(source) {
+            Err(SceneError::Invalid { line, key, .. }) => (line, key),
+            Err(e) => panic!("Unexpected error {e}"),
+            Ok(_) => panic!("Expected an error"),
+        }
+    }
+
+    #[test]
+    fn loading_a_scene() {
+        let scene = parse_scene(SCENE).unwrap();
+        assert_eq!(scene.camera.hsize(), 40);
+        assert_eq!(scene.camera.vsize(), 20);
+        assert!(scene.camera.fov().eps_eq(0.785));
+        assert_eq!(scene.world.lights().len(), 1);
+
+        let objects = scene.world.objetcs();
+        assert_eq!(objects.len(), 2);
+        let sphere = &objects[0];
+        assert_eq!(
+            sphere.transform(),
+            &(translation(1.0, 2.0, 3.0) * scaling(0.5, 0.5, 0.5))
+        );
+        let m = sphere.material();
+        assert!(m.diffuse.eps_eq(0.7));
+        assert!(m.reflective.eps_eq(0.2));
+        assert_eq!(m.color.at(&point(0, 0, 0)), color(0.5, 0.6, 0.9));
+
+        let plane = &objects[1];
+        assert_eq!(plane.material().color.at(&point(1.5, 0, 0)), color(0, 0, 0));
+    }
+
+    #[test]
+    fn composite_shapes_and_lights() {
+        let scene = parse_scene(
+            "
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+- add: light
+  corner: [-1, 2, 4]
+  uvec: [2, 0, 0]
+  vvec: [0, 2, 0]
+  usteps: 4
+  vsteps: 2
+  jitter: 7
+  intensity: [1, 1, 1]
+- add: light
+  type: spot
+  at: [0, 5, 0]
+  direction: [0, -1, 0]
+  inner-angle: 0.3
+  outer-angle: 0.5
+  intensity: [1, 1, 1]
+  attenuation: inverse-square
+- add: group
+  transform:
+    - [rotate-y, 1.5707963267948966]
+  children:
+    - add: cylinder
+      min: 0
+      max: 2
+      closed: true
+    - add: csg
+      operation: difference
+      left:
+        add: cube
+      right:
+        add: sphere
+        transform:
+          - [translate, 0, 1, 0]
+",
+        )
+        .unwrap();
+        assert_eq!(scene.world.lights().len(), 2);
+        assert!(format!("{:?}", scene.world.lights()[0]).contains("jitter: Some(7)"));
+
+        let objects = scene.world.objetcs();
+        assert_eq!(objects.len(), 1);
+        let b = objects[0].bounds();
+        assert_eq!(b.min(), point(-1, -1, -1));
+        assert_eq!(b.max(), point(1, 2, 1));
+    }
+
+    #[test]
+    fn errors_point_at_key_and_line() {
+        let camera = "- add: camera\n  width: 10\n  height: 10\n  field-of-view: 1\n  from: [0, 0, -5]\n  to: [0, 0, 0]\n";
+
+        let scene = format!("{camera}- add: sphere\n  materail:\n    diffuse: 1\n");
+        assert_eq!(error(&scene), (8, "materail".to_string()));
+
+        let scene = format!("{camera}- add: sphere\n  material:\n    diffuse: high\n");
+        assert_eq!(error(&scene), (9, "diffuse".to_string()));
+
+        let scene = format!("{camera}- add: cube\n  transform:\n    - [translate, 1, 2]\n");
+        assert_eq!(error(&scene), (9, "transform".to_string()));
+
+        let scene = format!(
+            "{camera}- add: cube\n  transform:\n    - [scale, 0.02, 0.02, 0.02]\n    - [scale, 0, 1, 1]\n"
+        );
+        assert_eq!(error(&scene), (10, "transform".to_string()));
+
+        let scene = format!("{camera}- add: cube\n  material: missing\n");
+        assert_eq!(error(&scene), (8, "material".to_string()));
+
+        let scene = format!("{camera}- add: torus\n");
+        assert_eq!(error(&scene), (7, "add".to_string()));
+
+        assert_eq!(
+            error("- add: camera\n  width: 10\n"),
+            (1, "height".to_string())
+        );
+        assert_eq!(error("- add: sphere\n"), (1, "camera".to_string()));
+        assert_eq!(error(&format!("{camera}{camera}")), (7, "add".to_string()));
+
+        let scene = format!(
+            "{camera}- add: light\n  type: directional\n  direction: [0, -1, 0]\n  intensity: [1, 1, 1]\n  attenuation: linear\n"
+        );
+        assert_eq!(error(&scene), (11, "attenuation".to_string()));
+
+        let scene = format!(
+            "{camera}- add: light\n  at: [0, 5, 0]\n  intensity: [1, 1, 1]\n  attenuation: [0, 0, 0]\n"
+        );
+        assert_eq!(error(&scene), (10, "attenuation".to_string()));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let e = parse_scene("- add: camera\n  width: [10\n").err().unwrap();
+        assert!(matches!(e, SceneError::Syntax { .. }));
+        assert!(e.to_string().starts_with("Line "));
+    }
+
+    #[test]
+    fn loading_obj_relative_to_scene() {
+        let dir = std::env::temp_dir().join(format!("scene_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("triangle.obj"),
+            "v -1 1 0\nv -1 0 0\nv 1 0 0\nf 1 2 3\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("scene.yml"),
+            "- add: camera\n  width: 10\n  height: 10\n  field-of-view: 1\n  from: [0, 0, -5]\n  to: [0, 0, 0]\n- add: obj\n  file: triangle.obj\n  transform:\n    - [translate, 0, 5, 0]\n",
+        )
+        .unwrap();
+
+        let scene = load_scene(dir.join("scene.yml"));
+        fs::remove_dir_all(&dir).unwrap();
+        let scene = scene.unwrap();
+        assert_eq!(scene.world.objetcs()[0].bounds().max(), point(1, 6, 0));
+
+        assert!(matches!(
+            load_scene(dir.join("scene.yml")),
+            Err(SceneError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn bundled_scenes_load() {
+        let scene = parse_scene(include_str!("../scenes/spheres.yml")).unwrap();
+        assert_eq!(scene.world.objetcs().len(), 6);
+        assert_eq!(scene.camera.hsize(), 1920);
+
+        let scene = parse_scene(include_str!("../scenes/plane.yml")).unwrap();
+        assert_eq!(scene.world.objetcs().len(), 4);
+    }
+}
@@ -0,0 +1,231 @@
+//! A YAML document tree that remembers the line of every node, so errors
+//! can point into the scene file.
+
+use std::collections::HashMap;
+
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
+
+use super::SceneError;
+
+#[derive(Debug, Clone)]
+pub(super) struct Node {
+    pub line: usize,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Value {
+    Scalar(String),
+    Seq(Vec<Node>),
+    Map(Vec<Entry>),
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Entry {
+    pub key: String,
+    pub line: usize,
+    pub value: Node,
+}
+
+impl Node {
+    pub fn as_scalar(&self) -> Option<&str> {
+        match &self.value {
+            Value::Scalar(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_seq(&self) -> Option<&[Node]> {
+        match &self.value {
+            Value::Seq(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&[Entry]> {
+        match &self.value {
+            Value::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the first document in `source`; an empty source is an empty list.
+pub(super) fn parse(source: &str) -> Result<Node, SceneError> {
+    let mut builder = Builder::default();
+    let mut parser = Parser::new(source.chars());
+    parser
+        .load(&mut builder, false)
+        .map_err(|e| SceneError::Syntax {
+            line: e.marker().line(),
+            message: e.to_string(),
+        })?;
+
+    if let Some(error) = builder.error {
+        return Err(error);
+    }
+    Ok(builder.root.unwrap_or(Node {
+        line: 1,
+        value: Value::Seq(Vec::new()),
+    }))
+}
+
+enum Frame {
+    Seq {
+        line: usize,
+        anchor: usize,
+        items: Vec<Node>,
+    },
+    Map {
+        line: usize,
+        anchor: usize,
+        entries: Vec<Entry>,
+        key: Option<(String, usize)>,
+    },
+}
+
+#[derive(Default)]
+struct Builder {
+    stack: Vec<Frame>,
+    anchors: HashMap<usize, Node>,
+    root: Option<Node>,
+    error: Option<SceneError>,
+}
+
+impl Builder {
+    fn finish(&mut self, node: Node, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+
+        match self.stack.last_mut() {
+            None => {
+                if self.root.is_none() {
+                    self.root = Some(node);
+                }
+            }
+            Some(Frame::Seq { items, .. }) => items.push(node),
+            Some(Frame::Map { entries, key, .. }) => match key.take() {
+                Some((key, line)) => entries.push(Entry {
+                    key,
+                    line,
+                    value: node,
+                }),
+                None => match node.value {
+                    Value::Scalar(s) => *key = Some((s, node.line)),
+                    _ => {
+                        self.error.get_or_insert(SceneError::Syntax {
+                            line: node.line,
+                            message: "Keys must be plain values".to_string(),
+                        });
+                    }
+                },
+            },
+        }
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        let line = mark.line();
+        match ev {
+            Event::Scalar(value, _, anchor, _) => {
+                let node = Node {
+                    line,
+                    value: Value::Scalar(value),
+                };
+                self.finish(node, anchor);
+            }
+            Event::Alias(anchor) => match self.anchors.get(&anchor) {
+                Some(node) => self.finish(node.clone(), 0),
+                None => {
+                    self.error.get_or_insert(SceneError::Syntax {
+                        line,
+                        message: "Unknown alias".to_string(),
+                    });
+                }
+            },
+            Event::SequenceStart(anchor) => self.stack.push(Frame::Seq {
+                line,
+                anchor,
+                items: Vec::new(),
+            }),
+            Event::MappingStart(anchor) => self.stack.push(Frame::Map {
+                line,
+                anchor,
+                entries: Vec::new(),
+                key: None,
+            }),
+            Event::SequenceEnd | Event::MappingEnd => {
+                let (node, anchor) = match self.stack.pop() {
+                    Some(Frame::Seq {
+                        line,
+                        anchor,
+                        items,
+                    }) => (
+                        Node {
+                            line,
+                            value: Value::Seq(items),
+                        },
+                        anchor,
+                    ),
+                    Some(Frame::Map {
+                        line,
+                        anchor,
+                        entries,
+                        ..
+                    }) => (
+                        Node {
+                            line,
+                            value: Value::Map(entries),
+                        },
+                        anchor,
+                    ),
+                    None => return,
+                };
+                self.finish(node, anchor);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Value};
+
+    #[test]
+    fn nodes_know_their_lines() {
+        let root = parse("- add: sphere\n  material:\n    diffuse: 0.5\n- [1, 2]\n").unwrap();
+        let items = root.as_seq().unwrap();
+        assert_eq!(items.len(), 2);
+
+        let entries = items[0].as_map().unwrap();
+        assert_eq!(entries[0].key, "add");
+        assert_eq!(entries[0].line, 1);
+        assert_eq!(entries[1].key, "material");
+        assert_eq!(entries[1].line, 2);
+        let material = entries[1].value.as_map().unwrap();
+        assert_eq!(material[0].line, 3);
+        assert_eq!(material[0].value.as_scalar(), Some("0.5"));
+
+        assert_eq!(items[1].line, 4);
+        assert!(matches!(&items[1].value, Value::Seq(v) if v.len() == 2));
+    }
+
+    #[test]
+    fn aliases_copy_anchored_nodes() {
+        let root = parse("a: &red [1, 0, 0]\nb: *red\n").unwrap();
+        let entries = root.as_map().unwrap();
+        assert_eq!(entries[1].value.as_seq().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn syntax_errors_have_lines() {
+        let e = parse("- add: sphere\n  material: [1, 2\n").unwrap_err();
+        assert!(matches!(e, super::SceneError::Syntax { line, .. } if line >= 2));
+    }
+}
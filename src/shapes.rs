@@ -1,63 +1,189 @@
+mod base;
+mod cone;
+pub use cone::Cone;
+mod cube;
+pub use cube::Cube;
+mod csg;
+pub use csg::{Csg, CsgOperation};
+mod cylinder;
+pub use cylinder::Cylinder;
+mod group;
+pub use group::Group;
+mod mesh;
+pub use mesh::Mesh;
 mod plane;
 pub use plane::Plane;
+mod smooth_triangle;
+pub use smooth_triangle::SmoothTriangle;
 mod sphere;
 pub use sphere::Sphere;
+mod triangle;
+pub use triangle::Triangle;
 
-use std::{fmt::Debug, sync::Mutex};
+#[cfg(test)]
+use std::sync::Mutex;
+use std::{borrow::Cow, fmt::Debug};
 
 use crate::{
+    bounds::BoundingBox,
     material::Material,
     matrix::helpers::Mat4,
     ray::{Intersection, Ray},
+    transformation::Motion,
     tuples::Tuple,
 };
 
-pub trait Shape: Debug {
+#[cfg_attr(feature = "serde", typetag::serde(tag = "type"))]
+pub trait Shape: Debug + Send + Sync {
     fn transform(&self) -> &Mat4;
     fn set_transform(&mut self, new_transform: Mat4);
     fn material(&self) -> &Material;
     fn material_mut(&mut self) -> &mut Material;
     fn set_material(&mut self, new_material: Material);
-    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection>;
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>>;
     fn local_normal_at(&self, p: Tuple) -> Tuple;
 
-    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
-        let object_ray = ray.transform(&self.transform().inverse());
+    /// Applies `edit` to the material. Groups, meshes and CSG shapes apply it
+    /// to their children's materials too, so only the fields `edit` touches
+    /// change there.
+    fn edit_material(&mut self, edit: &dyn Fn(&mut Material)) {
+        edit(self.material_mut());
+    }
+
+    /// The keyframed transform of a moving shape. When set, it replaces
+    /// `transform()` for rays sent at a time.
+    fn motion(&self) -> Option<&Motion> {
+        None
+    }
+
+    /// Shapes that can move store the motion and return it from `motion()`;
+    /// by default it is ignored and the shape stays put.
+    fn set_motion(&mut self, _motion: Option<Motion>) {}
+
+    /// The inverse of `transform()`. Shapes that cache it should override this.
+    fn inverse(&self) -> Cow<'_, Mat4> {
+        Cow::Owned(self.transform().inverse())
+    }
+
+    /// The transposed inverse of `transform()`, used to transform normals.
+    fn inverse_transpose(&self) -> Cow<'_, Mat4> {
+        Cow::Owned(self.inverse().transpose())
+    }
+
+    /// The bounds in object space. Shapes without an override are treated as
+    /// infinite, so they are always tested.
+    fn local_bounds(&self) -> BoundingBox {
+        BoundingBox::infinite()
+    }
+
+    /// Whether `other` is this shape or, for groups and CSG shapes, one of
+    /// its descendants.
+    fn includes(&self, other: &dyn Shape) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+
+    /// The inverse of the transform at `time`.
+    fn inverse_at(&self, time: f64) -> Cow<'_, Mat4> {
+        match self.motion() {
+            Some(motion) => Cow::Owned(motion.at(time).inverse()),
+            None => self.inverse(),
+        }
+    }
+
+    /// The bounds in world space, covering the whole motion for moving
+    /// shapes.
+    fn bounds(&self) -> BoundingBox {
+        match self.motion() {
+            Some(motion) => motion.bounds(&self.local_bounds()),
+            None => self.local_bounds().transform(self.transform()),
+        }
+    }
+
+    /// A shape with a singular transform has NaNs for an inverse, giving NaN
+    /// distances, which are dropped here.
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let object_ray = ray.transform(&self.inverse_at(ray.time()));
         self.local_intersect(&object_ray)
+            .into_iter()
+            .filter(|i| !i.t().is_nan())
+            .map(|i| i.with_time(ray.time()))
+            .collect()
+    }
+
+    /// The object space normal for a specific hit. Shapes that need the
+    /// hit's `u`/`v` (like smooth triangles) override this.
+    fn local_normal_at_hit(&self, p: Tuple, _hit: &Intersection) -> Tuple {
+        self.local_normal_at(p)
+    }
+
+    /// Converts a world space point into this shape's object space.
+    fn world_to_object(&self, p: Tuple) -> Tuple {
+        self.world_to_object_at(p, 0.0)
+    }
+
+    /// Converts a world space point into this shape's object space, as the
+    /// shape is placed at `time`.
+    fn world_to_object_at(&self, p: Tuple, time: f64) -> Tuple {
+        &*self.inverse_at(time) * p
     }
 
     fn normal_at(&self, p: Tuple) -> Tuple {
-        let object_point = self.transform().inverse() * p;
-        let object_normal = self.local_normal_at(object_point);
-        let world_normal = self.transform().inverse().transpose() * object_normal;
+        let object_point = self.world_to_object(p);
+        self.normal_to_world(self.local_normal_at(object_point))
+    }
+
+    fn normal_at_hit(&self, p: Tuple, hit: &Intersection) -> Tuple {
+        let object_point = self.world_to_object_at(p, hit.time());
+        self.normal_to_world_at(self.local_normal_at_hit(object_point, hit), hit.time())
+    }
+
+    fn normal_to_world(&self, object_normal: Tuple) -> Tuple {
+        self.normal_to_world_at(object_normal, 0.0)
+    }
+
+    fn normal_to_world_at(&self, object_normal: Tuple, time: f64) -> Tuple {
+        let world_normal = match self.motion() {
+            Some(_) => &self.inverse_at(time).transpose() * object_normal,
+            None => &*self.inverse_transpose() * object_normal,
+        };
 
         (Tuple::vector(world_normal.x(), world_normal.y(), world_normal.z())).normalized()
     }
 }
 
+#[cfg(test)]
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TestShape {
     transformation: Mat4,
+    motion: Option<Motion>,
     material: Material,
+    #[cfg_attr(feature = "serde", serde(skip))]
     saved_ray: Mutex<Option<Ray>>,
 }
 
+#[cfg(test)]
 impl PartialEq for TestShape {
     fn eq(&self, other: &Self) -> bool {
         self.transformation == other.transformation && self.material == other.material
     }
 }
 
+#[cfg(test)]
+#[allow(clippy::new_without_default)]
 impl TestShape {
     pub fn new() -> TestShape {
         Self {
             transformation: Mat4::identity(),
+            motion: None,
             material: Material::default(),
             saved_ray: Mutex::new(None),
         }
     }
 }
 
+#[cfg(test)]
+#[cfg_attr(feature = "serde", typetag::serde(name = "test-shape"))]
 impl Shape for TestShape {
     fn transform(&self) -> &Mat4 {
         &self.transformation
@@ -67,6 +193,14 @@ impl Shape for TestShape {
         self.transformation = new_transform;
     }
 
+    fn motion(&self) -> Option<&Motion> {
+        self.motion.as_ref()
+    }
+
+    fn set_motion(&mut self, motion: Option<Motion>) {
+        self.motion = motion;
+    }
+
     fn material(&self) -> &Material {
         &self.material
     }
@@ -79,9 +213,9 @@ impl Shape for TestShape {
         self.material = new_material;
     }
 
-    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
         let mut m = self.saved_ray.lock().unwrap();
-        *m = Some(Ray::new(ray.origin(), ray.direction()));
+        *m = Some(Ray::new(ray.origin(), ray.direction()).with_time(ray.time()));
 
         vec![]
     }
@@ -96,10 +230,14 @@ mod tests {
     use crate::{
         material::Material,
         matrix::helpers::Mat4,
-        ray::Ray,
+        ray::{Intersection, Ray},
         shapes::Shape,
-        transformation::{helper::TransformationBuilder, scaling, translation, PI},
-        tuples::helpers::{point, vector},
+        shapes::Sphere,
+        transformation::{helper::TransformationBuilder, scaling, translation, Motion, PI},
+        tuples::{
+            helpers::{point, vector},
+            Tuple,
+        },
     };
 
     use super::TestShape;
@@ -126,6 +264,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn assigning_material() {
         let mut s = TestShape::new();
         let mut m = Material::default();
@@ -135,6 +274,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn intersecting_scaled_ray() {
         let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
         let mut s = TestShape::new();
@@ -152,6 +292,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn intersecting_translated_ray() {
         let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
         let mut s = TestShape::new();
@@ -169,6 +310,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn normal_on_translated() {
         let mut s = TestShape::new();
         s.set_transform(translation(0.0, 1.0, 0.0));
@@ -187,4 +329,146 @@ mod tests {
         let n = s.normal_at(point(0.0, 2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0));
         assert_eq!(n, vector(0, 0.97014, -0.24254));
     }
+
+    #[test]
+    fn intersecting_moving_shape() {
+        let mut s = TestShape::new();
+        s.set_transform(translation(5.0, 0.0, 0.0));
+        s.set_motion(Some(Motion::linear(
+            Mat4::identity(),
+            translation(0.0, 2.0, 0.0),
+        )));
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1)).with_time(0.5);
+        s.intersect(&r);
+
+        let saved = s.saved_ray.lock().unwrap().take().unwrap();
+        assert_eq!(saved.origin(), point(0, -1, -5));
+        assert_eq!(saved.time(), 0.5);
+    }
+
+    #[test]
+    fn shapes_without_motion_ignore_it() {
+        #[derive(Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        struct Still {
+            transform: Mat4,
+            material: Material,
+        }
+
+        #[cfg_attr(feature = "serde", typetag::serde)]
+        impl Shape for Still {
+            fn transform(&self) -> &Mat4 {
+                &self.transform
+            }
+            fn set_transform(&mut self, new_transform: Mat4) {
+                self.transform = new_transform;
+            }
+            fn material(&self) -> &Material {
+                &self.material
+            }
+            fn material_mut(&mut self) -> &mut Material {
+                &mut self.material
+            }
+            fn set_material(&mut self, new_material: Material) {
+                self.material = new_material;
+            }
+            fn local_intersect(&self, _: &Ray) -> Vec<Intersection<'_>> {
+                vec![]
+            }
+            fn local_normal_at(&self, p: Tuple) -> Tuple {
+                Tuple::vector(p.x(), p.y(), p.z())
+            }
+        }
+
+        let mut s = Still {
+            transform: translation(1.0, 0.0, 0.0),
+            material: Material::default(),
+        };
+        s.set_motion(Some(Motion::linear(
+            Mat4::identity(),
+            translation(0.0, 2.0, 0.0),
+        )));
+        assert!(s.motion().is_none());
+        assert_eq!(*s.inverse_at(1.0), translation(1.0, 0.0, 0.0).inverse());
+    }
+
+    #[test]
+    fn small_and_singular_transforms() {
+        let mut s = Sphere::new();
+        s.set_transform(scaling(0.02, 0.02, 0.02));
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let xs = s.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(s.normal_at(point(0, 0, -0.02)), vector(0, 0, -1));
+
+        s.set_transform(scaling(0.0, 1.0, 1.0));
+        assert!(s.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn moving_sphere_hits_and_normals() {
+        let mut s = Sphere::new();
+        s.set_motion(Some(Motion::linear(
+            Mat4::identity(),
+            translation(4.0, 0.0, 0.0),
+        )));
+        let r = Ray::new(point(4, 0, -5), vector(0, 0, 1));
+        assert!(s.intersect(&r).is_empty());
+
+        let r = r.with_time(1.0);
+        let xs = s.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].time(), 1.0);
+        assert_eq!(s.normal_at_hit(r.at(xs[0].t()), &xs[0]), vector(0, 0, -1));
+        assert_eq!(s.bounds().max(), point(5, 1, 1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn shapes_round_trip_through_json() {
+        use std::sync::Arc;
+
+        use crate::{
+            patterns::CheckerPattern,
+            shapes::{Cone, Csg, Cube, Group, Triangle},
+            tuples::helpers::color,
+        };
+
+        let mut sphere = Sphere::new();
+        sphere.set_transform(translation(1.0, 2.0, 3.0));
+        sphere.set_motion(Some(Motion::linear(
+            Mat4::identity(),
+            translation(0.0, 1.0, 0.0),
+        )));
+        sphere.set_material(Material {
+            color: Arc::new(CheckerPattern::new(color(1, 0, 0), color(0, 0, 1))),
+            reflective: 0.5,
+            ..Default::default()
+        });
+
+        let mut group = Group::new();
+        group.set_transform(scaling(2.0, 2.0, 2.0));
+        group.add_child(Box::new(Cone::truncated(-1.0, 0.0, true)));
+        group.add_child(Box::new(Csg::difference(
+            Box::new(Cube::new()),
+            Box::new(Triangle::new(
+                point(0, 1, 0),
+                point(-1, 0, 0),
+                point(1, 0, 0),
+            )),
+        )));
+
+        let shapes: Vec<Box<dyn Shape>> = vec![Box::new(sphere), Box::new(group)];
+        let json = serde_json::to_string(&shapes).unwrap();
+        let loaded: Vec<Box<dyn Shape>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+        assert!(json.starts_with(r#"[{"type":"sphere","#));
+
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        for (a, b) in shapes.iter().zip(&loaded) {
+            let ts =
+                |xs: Vec<crate::ray::Intersection>| xs.iter().map(|i| i.t()).collect::<Vec<_>>();
+            assert_eq!(ts(a.intersect(&r)), ts(b.intersect(&r)));
+        }
+    }
 }
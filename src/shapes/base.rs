@@ -0,0 +1,73 @@
+use crate::{
+    material::Material,
+    transformation::{Motion, Transform},
+};
+
+/// The state every shape carries: where it is placed, how it moves and what
+/// it is made of. Shapes keep it in a `base` field and get the matching
+/// `Shape` methods from `shape_base!`.
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ShapeBase {
+    pub transform: Transform,
+    pub motion: Option<Motion>,
+    pub material: Material,
+}
+
+impl ShapeBase {
+    pub fn new() -> ShapeBase {
+        ShapeBase {
+            transform: Transform::identity(),
+            motion: None,
+            material: Material::default(),
+        }
+    }
+}
+
+/// Implements the transform, motion and material methods of `Shape` on top
+/// of the `base: ShapeBase` field. `shape_base!(getters)` leaves out
+/// `set_transform`, `set_motion` and `set_material`, for containers that
+/// pass those on to their children.
+macro_rules! shape_base {
+    () => {
+        $crate::shapes::base::shape_base!(getters);
+
+        fn set_transform(&mut self, new_transform: $crate::matrix::helpers::Mat4) {
+            self.base.transform = $crate::transformation::Transform::new(new_transform);
+        }
+
+        fn set_motion(&mut self, motion: Option<$crate::transformation::Motion>) {
+            self.base.motion = motion;
+        }
+
+        fn set_material(&mut self, new_material: $crate::material::Material) {
+            self.base.material = new_material;
+        }
+    };
+    (getters) => {
+        fn transform(&self) -> &$crate::matrix::helpers::Mat4 {
+            self.base.transform.matrix()
+        }
+
+        fn inverse(&self) -> std::borrow::Cow<'_, $crate::matrix::helpers::Mat4> {
+            std::borrow::Cow::Borrowed(self.base.transform.inverse())
+        }
+
+        fn inverse_transpose(&self) -> std::borrow::Cow<'_, $crate::matrix::helpers::Mat4> {
+            std::borrow::Cow::Borrowed(self.base.transform.inverse_transpose())
+        }
+
+        fn motion(&self) -> Option<&$crate::transformation::Motion> {
+            self.base.motion.as_ref()
+        }
+
+        fn material(&self) -> &$crate::material::Material {
+            &self.base.material
+        }
+
+        fn material_mut(&mut self) -> &mut $crate::material::Material {
+            &mut self.base.material
+        }
+    };
+}
+pub(crate) use shape_base;
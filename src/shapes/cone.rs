@@ -0,0 +1,201 @@
+use crate::{
+    bounds::BoundingBox,
+    ray::{Intersection, Ray},
+    tuples::{helpers::point, FEquals, Tuple, EPSILON},
+};
+
+use super::{
+    base::{shape_base, ShapeBase},
+    cylinder::check_cap,
+    Shape,
+};
+
+/// A double-napped cone around the y axis with its tip at the origin,
+/// optionally truncated to `minimum..maximum` and capped when `closed` is set.
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cone {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cone {
+    pub fn new() -> Cone {
+        Self {
+            base: ShapeBase::new(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+
+    pub fn truncated(minimum: f64, maximum: f64, closed: bool) -> Cone {
+        Self {
+            minimum,
+            maximum,
+            closed,
+            ..Self::new()
+        }
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || ray.direction().y().eps_eq(0.0) {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin().y()) / ray.direction().y();
+            if check_cap(ray, t, y.abs()) {
+                xs.push(Intersection::new(t, self));
+            }
+        }
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "cone"))]
+impl Shape for Cone {
+    shape_base!();
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let (o, d) = (ray.origin(), ray.direction());
+        let mut xs = Vec::new();
+
+        let a = d.x() * d.x() - d.y() * d.y() + d.z() * d.z();
+        let b = 2.0 * o.x() * d.x() - 2.0 * o.y() * d.y() + 2.0 * o.z() * d.z();
+        let c = o.x() * o.x() - o.y() * o.y() + o.z() * o.z();
+
+        let ts = if a.eps_eq(0.0) {
+            // parallel to one of the halves, so there is at most one hit
+            if b.eps_eq(0.0) {
+                vec![]
+            } else {
+                vec![-c / (2.0 * b)]
+            }
+        } else {
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return xs;
+            }
+
+            let disc_sqrt = discriminant.sqrt();
+            let t0 = (-b - disc_sqrt) / (2.0 * a);
+            let t1 = (-b + disc_sqrt) / (2.0 * a);
+            vec![t0.min(t1), t0.max(t1)]
+        };
+
+        for t in ts {
+            let y = o.y() + t * d.y();
+            if self.minimum < y && y < self.maximum {
+                xs.push(Intersection::new(t, self));
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+        xs
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        let r = self.minimum.abs().max(self.maximum.abs());
+        BoundingBox::new(point(-r, self.minimum, -r), point(r, self.maximum, r))
+    }
+
+    fn local_normal_at(&self, p: Tuple) -> Tuple {
+        let dist = p.x() * p.x() + p.z() * p.z();
+
+        if dist < self.maximum * self.maximum && p.y() >= self.maximum - EPSILON {
+            Tuple::vector(0.0, 1.0, 0.0)
+        } else if dist < self.minimum * self.minimum && p.y() <= self.minimum + EPSILON {
+            Tuple::vector(0.0, -1.0, 0.0)
+        } else {
+            let y = dist.sqrt();
+            Tuple::vector(p.x(), if p.y() > 0.0 { -y } else { y }, p.z())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::Ray,
+        shapes::Shape,
+        tuples::{
+            helpers::{point, vector},
+            FEquals,
+        },
+    };
+
+    use super::Cone;
+
+    #[test]
+    fn intersecting_cone() {
+        let shape = Cone::new();
+        let cases = [
+            (point(0, 0, -5), vector(0, 0, 1), 5.0, 5.0),
+            (point(0, 0, -5), vector(1, 1, 1), 8.66025, 8.66025),
+            (point(1, 1, -5), vector(-0.5, -1, 1), 4.55006, 49.44994),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let r = Ray::new(origin, direction.normalized());
+            let xs = shape.local_intersect(&r);
+            assert_eq!(xs.len(), 2);
+            assert!((xs[0].t() - t0).abs() < 0.0001);
+            assert!((xs[1].t() - t1).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn ray_parallel_to_one_half() {
+        let shape = Cone::new();
+        let r = Ray::new(point(0, 0, -1), vector(0, 1, 1).normalized());
+        let xs = shape.local_intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].t().eps_eq(0.35355));
+    }
+
+    #[test]
+    fn intersecting_cone_caps() {
+        let shape = Cone::truncated(-0.5, 0.5, true);
+        let cases = [
+            (point(0, 0, -5), vector(0, 1, 0), 0),
+            (point(0, 0, -0.25), vector(0, 1, 1), 2),
+            (point(0, 0, -0.25), vector(0, 1, 0), 4),
+        ];
+
+        for (origin, direction, count) in cases {
+            let r = Ray::new(origin, direction.normalized());
+            assert_eq!(shape.local_intersect(&r).len(), count);
+        }
+    }
+
+    #[test]
+    fn normal_on_cone() {
+        let shape = Cone::new();
+        let cases = [
+            (point(0, 0, 0), vector(0, 0, 0)),
+            (point(1, 1, 1), vector(1, -(2.0_f64.sqrt()), 1)),
+            (point(-1, -1, 0), vector(-1, 1, 0)),
+        ];
+
+        for (p, normal) in cases {
+            assert_eq!(shape.local_normal_at(p), normal);
+        }
+    }
+
+    #[test]
+    fn bounds_of_truncated_cone() {
+        let c = Cone::truncated(-5.0, 3.0, false);
+        assert_eq!(c.local_bounds().min(), point(-5, -5, -5));
+        assert_eq!(c.local_bounds().max(), point(5, 3, 5));
+        assert!(!Cone::new().local_bounds().is_finite());
+    }
+}
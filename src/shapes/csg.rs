@@ -0,0 +1,330 @@
+use crate::{
+    bounds::BoundingBox,
+    material::Material,
+    matrix::helpers::Mat4,
+    ray::{Intersection, Ray},
+    transformation::{Motion, Transform},
+    tuples::Tuple,
+};
+
+use super::{
+    base::{shape_base, ShapeBase},
+    group::Placement,
+    Shape,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    /// Whether a hit on the left (`left_hit`) or right shape is part of the
+    /// combined surface, given whether the ray is currently inside the left
+    /// (`in_left`) and right (`in_right`) shape.
+    pub fn allows(self, left_hit: bool, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+            CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+            CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left),
+        }
+    }
+}
+
+/// Constructive solid geometry: two shapes combined by a `CsgOperation`.
+///
+/// Like a `Group`, the children store their full object-to-world transform,
+/// so the transform and motion given to the `Csg` are applied to them.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Csg {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
+    operation: CsgOperation,
+    left: Box<dyn Shape>,
+    right: Box<dyn Shape>,
+    placements: [Placement; 2],
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Box<dyn Shape>, right: Box<dyn Shape>) -> Csg {
+        Csg {
+            base: ShapeBase::new(),
+            operation,
+            placements: [Placement::of(&*left), Placement::of(&*right)],
+            left,
+            right,
+        }
+    }
+    pub fn union(left: Box<dyn Shape>, right: Box<dyn Shape>) -> Csg {
+        Csg::new(CsgOperation::Union, left, right)
+    }
+    pub fn intersection(left: Box<dyn Shape>, right: Box<dyn Shape>) -> Csg {
+        Csg::new(CsgOperation::Intersection, left, right)
+    }
+    pub fn difference(left: Box<dyn Shape>, right: Box<dyn Shape>) -> Csg {
+        Csg::new(CsgOperation::Difference, left, right)
+    }
+
+    pub fn operation(&self) -> CsgOperation {
+        self.operation
+    }
+    pub fn left(&self) -> &dyn Shape {
+        &*self.left
+    }
+    pub fn right(&self) -> &dyn Shape {
+        &*self.right
+    }
+
+    fn place_children(&mut self) {
+        let transform = self.base.transform.matrix();
+        let [left, right] = &self.placements;
+        left.apply(&mut *self.left, transform, self.base.motion.as_ref());
+        right.apply(&mut *self.right, transform, self.base.motion.as_ref());
+    }
+
+    /// Keeps the intersections (sorted by `t`) that lie on the combined surface.
+    pub fn filter_intersections<'a>(&self, xs: Vec<Intersection<'a>>) -> Vec<Intersection<'a>> {
+        let mut in_left = false;
+        let mut in_right = false;
+
+        xs.into_iter()
+            .filter(|i| {
+                let left_hit = self.left.includes(i.object());
+                let allowed = self.operation.allows(left_hit, in_left, in_right);
+
+                if left_hit {
+                    in_left = !in_left;
+                } else {
+                    in_right = !in_right;
+                }
+
+                allowed
+            })
+            .collect()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "csg"))]
+impl Shape for Csg {
+    shape_base!(getters);
+
+    fn set_transform(&mut self, new_transform: Mat4) {
+        self.base.transform = Transform::new(new_transform);
+        self.place_children();
+    }
+
+    /// Moves both children along, like `Group::set_motion`.
+    fn set_motion(&mut self, motion: Option<Motion>) {
+        self.base.motion = motion;
+        self.place_children();
+    }
+
+    /// Edits both children's materials along with the CSG's own.
+    fn edit_material(&mut self, edit: &dyn Fn(&mut Material)) {
+        self.left.edit_material(edit);
+        self.right.edit_material(edit);
+        edit(&mut self.base.material);
+    }
+
+    fn set_material(&mut self, new_material: Material) {
+        self.left.set_material(new_material.clone());
+        self.right.set_material(new_material.clone());
+        self.base.material = new_material;
+    }
+
+    /// The children are stored in world space, so the ray is not transformed.
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        if !self.bounds().intersects(ray) {
+            return Vec::new();
+        }
+
+        let mut xs = self.left.intersect(ray);
+        xs.append(&mut self.right.intersect(ray));
+        xs.sort_by(|a, b| a.t().total_cmp(&b.t()));
+        self.filter_intersections(xs)
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        self.intersect(&ray.transform(self.transform()))
+    }
+
+    /// Hits always refer to one of the children, never to the CSG itself.
+    fn local_normal_at(&self, _: Tuple) -> Tuple {
+        panic!("Normals of a CSG shape are computed by its children")
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = self.left.bounds();
+        bounds.merge(&self.right.bounds());
+        bounds
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        self.bounds().transform(&self.inverse())
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.left.includes(other) || self.right.includes(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::{Intersection, Ray},
+        shapes::{Cube, Cylinder, Group, Shape, Sphere},
+        transformation::{scaling, translation},
+        tuples::{
+            helpers::{point, vector},
+            FEquals,
+        },
+    };
+
+    use super::{Csg, CsgOperation};
+
+    #[test]
+    fn creating_csg() {
+        let c = Csg::union(Box::new(Sphere::new()), Box::new(Cube::new()));
+        assert_eq!(c.operation(), CsgOperation::Union);
+        assert!(c.includes(c.left()));
+        assert!(c.includes(c.right()));
+        assert!(!c.left().includes(c.right()));
+    }
+
+    #[test]
+    fn material_edits_reach_children() {
+        let mut c = Csg::difference(Box::new(Sphere::new()), Box::new(Cube::new()));
+        c.edit_material(&|m| m.transparency = 0.25);
+        assert_eq!(c.left().material().transparency, 0.25);
+        assert_eq!(c.right().material().transparency, 0.25);
+    }
+
+    #[test]
+    fn operation_rules() {
+        use CsgOperation::*;
+        #[rustfmt::skip]
+        let cases = [
+            (Union, true, true, true, false),
+            (Union, true, true, false, true),
+            (Union, true, false, true, false),
+            (Union, true, false, false, true),
+            (Union, false, true, true, false),
+            (Union, false, true, false, false),
+            (Union, false, false, true, true),
+            (Union, false, false, false, true),
+            (Intersection, true, true, true, true),
+            (Intersection, true, true, false, false),
+            (Intersection, true, false, true, true),
+            (Intersection, true, false, false, false),
+            (Intersection, false, true, true, true),
+            (Intersection, false, true, false, true),
+            (Intersection, false, false, true, false),
+            (Intersection, false, false, false, false),
+            (Difference, true, true, true, false),
+            (Difference, true, true, false, true),
+            (Difference, true, false, true, false),
+            (Difference, true, false, false, true),
+            (Difference, false, true, true, true),
+            (Difference, false, true, false, true),
+            (Difference, false, false, true, false),
+            (Difference, false, false, false, false),
+        ];
+        for (op, lhit, inl, inr, expected) in cases {
+            assert_eq!(
+                op.allows(lhit, inl, inr),
+                expected,
+                "{op:?} {lhit} {inl} {inr}"
+            );
+        }
+    }
+
+    #[test]
+    fn filtering_intersections() {
+        let cases = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+        for (op, x0, x1) in cases {
+            let c = Csg::new(op, Box::new(Sphere::new()), Box::new(Cube::new()));
+            let (s1, s2) = (c.left(), c.right());
+            let xs = vec![
+                Intersection::new(1.0, s1),
+                Intersection::new(2.0, s2),
+                Intersection::new(3.0, s1),
+                Intersection::new(4.0, s2),
+            ];
+            let result = c.filter_intersections(xs.clone());
+            assert_eq!(result.len(), 2);
+            assert!(result[0].t().eps_eq(xs[x0].t()));
+            assert!(result[1].t().eps_eq(xs[x1].t()));
+        }
+    }
+
+    #[test]
+    fn ray_misses() {
+        let c = Csg::union(Box::new(Sphere::new()), Box::new(Cube::new()));
+        let r = Ray::new(point(0, 2, -5), vector(0, 0, 1));
+        assert!(c.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn ray_hits() {
+        let mut s2 = Sphere::new();
+        s2.set_transform(translation(0.0, 0.0, 0.5));
+        let c = Csg::union(Box::new(Sphere::new()), Box::new(s2));
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let xs = c.intersect(&r);
+
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t().eps_eq(4.0));
+        assert!(std::ptr::addr_eq(xs[0].object(), c.left()));
+        assert!(xs[1].t().eps_eq(6.5));
+        assert!(std::ptr::addr_eq(xs[1].object(), c.right()));
+    }
+
+    #[test]
+    fn children_in_groups() {
+        let g = Group::new().with_child(Sphere::new());
+        let c = Csg::difference(Box::new(g), Box::new(Cube::new()));
+        let xs = c
+            .left()
+            .intersect(&Ray::new(point(0, 0, -5), vector(0, 0, 1)));
+        let child = xs[0].object();
+        assert!(c.left().includes(child));
+        assert!(!c.right().includes(child));
+    }
+
+    #[test]
+    fn drilled_block() {
+        let mut drill = Cylinder::truncated(-2.0, 2.0, true);
+        drill.set_transform(scaling(0.5, 1.0, 0.5));
+        let mut block = Csg::difference(Box::new(Cube::new()), Box::new(drill));
+        block.set_transform(translation(3.0, 0.0, 0.0));
+
+        let through_hole = Ray::new(point(3, 5, 0), vector(0, -1, 0));
+        assert!(block.intersect(&through_hole).is_empty());
+
+        let r = Ray::new(point(3.75, 5, 0), vector(0, -1, 0));
+        let xs = block.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t().eps_eq(4.0));
+        assert_eq!(xs[0].object().normal_at(r.at(4.0)), vector(0, 1, 0));
+
+        let r = Ray::new(point(-2, 0, 0), vector(1, 0, 0));
+        let xs = block.intersect(&r);
+        let ts: Vec<_> = xs.iter().map(|i| i.t()).collect();
+        assert_eq!(ts.len(), 4);
+        for (t, expected) in ts.iter().zip([4.0, 4.5, 5.5, 6.0]) {
+            assert!(t.eps_eq(expected));
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{
+    bounds::{slab, BoundingBox},
+    ray::{Intersection, Ray},
+    tuples::{helpers::point, Tuple},
+};
+
+use super::{
+    base::{shape_base, ShapeBase},
+    Shape,
+};
+
+/// An axis-aligned cube spanning -1..1 on every axis.
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cube {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
+}
+
+impl Cube {
+    pub fn new() -> Cube {
+        Self {
+            base: ShapeBase::new(),
+        }
+    }
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Intersects a ray with the pair of planes at -1 and 1 on one axis, the same
+/// way bounding boxes do, so a ray accepted by the bounds is tested alike.
+fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
+    slab(origin, direction, -1.0, 1.0)
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "cube"))]
+impl Shape for Cube {
+    shape_base!();
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let (xtmin, xtmax) = check_axis(ray.origin().x(), ray.direction().x());
+        let (ytmin, ytmax) = check_axis(ray.origin().y(), ray.direction().y());
+        let (ztmin, ztmax) = check_axis(ray.origin().z(), ray.direction().z());
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            return Vec::new();
+        }
+
+        vec![Intersection::new(tmin, self), Intersection::new(tmax, self)]
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        BoundingBox::new(point(-1, -1, -1), point(1, 1, 1))
+    }
+
+    fn local_normal_at(&self, p: Tuple) -> Tuple {
+        let (ax, ay, az) = (p.x().abs(), p.y().abs(), p.z().abs());
+        let maxc = ax.max(ay).max(az);
+
+        if maxc == ax {
+            Tuple::vector(p.x(), 0.0, 0.0)
+        } else if maxc == ay {
+            Tuple::vector(0.0, p.y(), 0.0)
+        } else {
+            Tuple::vector(0.0, 0.0, p.z())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::Ray,
+        shapes::Shape,
+        tuples::{
+            helpers::{point, vector},
+            FEquals,
+        },
+    };
+
+    use super::Cube;
+
+    #[test]
+    fn ray_intersects_cube() {
+        let c = Cube::new();
+        let cases = [
+            (point(5, 0.5, 0), vector(-1, 0, 0), 4.0, 6.0),
+            (point(-5, 0.5, 0), vector(1, 0, 0), 4.0, 6.0),
+            (point(0.5, 5, 0), vector(0, -1, 0), 4.0, 6.0),
+            (point(0.5, -5, 0), vector(0, 1, 0), 4.0, 6.0),
+            (point(0.5, 0, 5), vector(0, 0, -1), 4.0, 6.0),
+            (point(0.5, 0, -5), vector(0, 0, 1), 4.0, 6.0),
+            (point(0, 0.5, 0), vector(0, 0, 1), -1.0, 1.0),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            let xs = c.local_intersect(&Ray::new(origin, direction));
+            assert_eq!(xs.len(), 2);
+            assert!(xs[0].t().eps_eq(t1));
+            assert!(xs[1].t().eps_eq(t2));
+        }
+    }
+
+    #[test]
+    fn ray_misses_cube() {
+        let c = Cube::new();
+        let cases = [
+            (point(-2, 0, 0), vector(0.2673, 0.5345, 0.8018)),
+            (point(0, -2, 0), vector(0.8018, 0.2673, 0.5345)),
+            (point(0, 0, -2), vector(0.5345, 0.8018, 0.2673)),
+            (point(2, 0, 2), vector(0, 0, -1)),
+            (point(0, 2, 2), vector(0, -1, 0)),
+            (point(2, 2, 0), vector(-1, 0, 0)),
+        ];
+
+        for (origin, direction) in cases {
+            let xs = c.local_intersect(&Ray::new(origin, direction));
+            assert_eq!(xs.len(), 0);
+        }
+    }
+
+    #[test]
+    fn near_axis_rays_agree_with_bounds() {
+        let c = Cube::new();
+        let r = Ray::new(point(0, 1.000001, -5), vector(0, -5e-6, 1));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t().eps_eq(4.0));
+        assert!(xs[1].t().eps_eq(6.0));
+        assert!(c.bounds().intersects(&r));
+
+        let r = Ray::new(point(0, 1.000001, -5), vector(0, 5e-6, 1));
+        assert!(c.local_intersect(&r).is_empty());
+        assert!(!c.bounds().intersects(&r));
+    }
+
+    #[test]
+    fn normal_on_cube() {
+        let c = Cube::new();
+        let cases = [
+            (point(1, 0.5, -0.8), vector(1, 0, 0)),
+            (point(-1, -0.2, 0.9), vector(-1, 0, 0)),
+            (point(-0.4, 1, -0.1), vector(0, 1, 0)),
+            (point(0.3, -1, -0.7), vector(0, -1, 0)),
+            (point(-0.6, 0.3, 1), vector(0, 0, 1)),
+            (point(0.4, 0.4, -1), vector(0, 0, -1)),
+            (point(1, 1, 1), vector(1, 0, 0)),
+            (point(-1, -1, -1), vector(-1, 0, 0)),
+        ];
+
+        for (p, normal) in cases {
+            assert_eq!(c.local_normal_at(p), normal);
+        }
+    }
+}
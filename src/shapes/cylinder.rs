@@ -0,0 +1,249 @@
+use crate::{
+    bounds::BoundingBox,
+    ray::{Intersection, Ray},
+    tuples::{helpers::point, FEquals, Tuple, EPSILON},
+};
+
+use super::{
+    base::{shape_base, ShapeBase},
+    Shape,
+};
+
+/// A cylinder of radius 1 around the y axis, optionally truncated to
+/// `minimum..maximum` and capped at both ends when `closed` is set.
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cylinder {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cylinder {
+    pub fn new() -> Cylinder {
+        Self {
+            base: ShapeBase::new(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+
+    pub fn truncated(minimum: f64, maximum: f64, closed: bool) -> Cylinder {
+        Self {
+            minimum,
+            maximum,
+            closed,
+            ..Self::new()
+        }
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || ray.direction().y().eps_eq(0.0) {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin().y()) / ray.direction().y();
+            if check_cap(ray, t, 1.0) {
+                xs.push(Intersection::new(t, self));
+            }
+        }
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks whether the ray at `t` lies within `radius` of the y axis.
+pub(super) fn check_cap(ray: &Ray, t: f64, radius: f64) -> bool {
+    let x = ray.origin().x() + t * ray.direction().x();
+    let z = ray.origin().z() + t * ray.direction().z();
+
+    x * x + z * z <= radius * radius + EPSILON
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "cylinder"))]
+impl Shape for Cylinder {
+    shape_base!();
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let (o, d) = (ray.origin(), ray.direction());
+        let mut xs = Vec::new();
+
+        let a = d.x() * d.x() + d.z() * d.z();
+        if !a.eps_eq(0.0) {
+            let b = 2.0 * o.x() * d.x() + 2.0 * o.z() * d.z();
+            let c = o.x() * o.x() + o.z() * o.z() - 1.0;
+            let discriminant = b * b - 4.0 * a * c;
+
+            if discriminant < 0.0 {
+                return xs;
+            }
+
+            let disc_sqrt = discriminant.sqrt();
+            let t0 = (-b - disc_sqrt) / (2.0 * a);
+            let t1 = (-b + disc_sqrt) / (2.0 * a);
+
+            for t in [t0.min(t1), t0.max(t1)] {
+                let y = o.y() + t * d.y();
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+        xs
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        BoundingBox::new(point(-1, self.minimum, -1), point(1, self.maximum, 1))
+    }
+
+    fn local_normal_at(&self, p: Tuple) -> Tuple {
+        let dist = p.x() * p.x() + p.z() * p.z();
+
+        if dist < 1.0 && p.y() >= self.maximum - EPSILON {
+            Tuple::vector(0.0, 1.0, 0.0)
+        } else if dist < 1.0 && p.y() <= self.minimum + EPSILON {
+            Tuple::vector(0.0, -1.0, 0.0)
+        } else {
+            Tuple::vector(p.x(), 0.0, p.z())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::Ray,
+        shapes::Shape,
+        tuples::{
+            helpers::{point, vector},
+            FEquals,
+        },
+    };
+
+    use super::Cylinder;
+
+    #[test]
+    fn ray_misses_cylinder() {
+        let cyl = Cylinder::new();
+        let cases = [
+            (point(1, 0, 0), vector(0, 1, 0)),
+            (point(0, 0, 0), vector(0, 1, 0)),
+            (point(0, 0, -5), vector(1, 1, 1)),
+        ];
+
+        for (origin, direction) in cases {
+            let r = Ray::new(origin, direction.normalized());
+            assert_eq!(cyl.local_intersect(&r).len(), 0);
+        }
+    }
+
+    #[test]
+    fn ray_strikes_cylinder() {
+        let cyl = Cylinder::new();
+        let cases = [
+            (point(1, 0, -5), vector(0, 0, 1), 5.0, 5.0),
+            (point(0, 0, -5), vector(0, 0, 1), 4.0, 6.0),
+            (point(0.5, 0, -5), vector(0.1, 1, 1), 6.80798, 7.08872),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let r = Ray::new(origin, direction.normalized());
+            let xs = cyl.local_intersect(&r);
+            assert_eq!(xs.len(), 2);
+            assert!(xs[0].t().eps_eq(t0));
+            assert!(xs[1].t().eps_eq(t1));
+        }
+    }
+
+    #[test]
+    fn normal_on_cylinder() {
+        let cyl = Cylinder::new();
+        let cases = [
+            (point(1, 0, 0), vector(1, 0, 0)),
+            (point(0, 5, -1), vector(0, 0, -1)),
+            (point(0, -2, 1), vector(0, 0, 1)),
+            (point(-1, 1, 0), vector(-1, 0, 0)),
+        ];
+
+        for (p, normal) in cases {
+            assert_eq!(cyl.local_normal_at(p), normal);
+        }
+    }
+
+    #[test]
+    fn default_cylinder_bounds() {
+        let cyl = Cylinder::new();
+        assert_eq!(cyl.minimum, f64::NEG_INFINITY);
+        assert_eq!(cyl.maximum, f64::INFINITY);
+        assert!(!cyl.closed);
+    }
+
+    #[test]
+    fn intersecting_truncated_cylinder() {
+        let cyl = Cylinder::truncated(1.0, 2.0, false);
+        let cases = [
+            (point(0, 1.5, 0), vector(0.1, 1, 0), 0),
+            (point(0, 3, -5), vector(0, 0, 1), 0),
+            (point(0, 0, -5), vector(0, 0, 1), 0),
+            (point(0, 2, -5), vector(0, 0, 1), 0),
+            (point(0, 1, -5), vector(0, 0, 1), 0),
+            (point(0, 1.5, -2), vector(0, 0, 1), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let r = Ray::new(origin, direction.normalized());
+            assert_eq!(cyl.local_intersect(&r).len(), count);
+        }
+    }
+
+    #[test]
+    fn intersecting_caps_of_closed_cylinder() {
+        let cyl = Cylinder::truncated(1.0, 2.0, true);
+        let cases = [
+            (point(0, 3, 0), vector(0, -1, 0), 2),
+            (point(0, 3, -2), vector(0, -1, 2), 2),
+            (point(0, 4, -2), vector(0, -1, 1), 2),
+            (point(0, 0, -2), vector(0, 1, 2), 2),
+            (point(0, -1, -2), vector(0, 1, 1), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let r = Ray::new(origin, direction.normalized());
+            assert_eq!(cyl.local_intersect(&r).len(), count);
+        }
+    }
+
+    #[test]
+    fn normal_on_cylinder_caps() {
+        let cyl = Cylinder::truncated(1.0, 2.0, true);
+        let cases = [
+            (point(0, 1, 0), vector(0, -1, 0)),
+            (point(0.5, 1, 0), vector(0, -1, 0)),
+            (point(0, 1, 0.5), vector(0, -1, 0)),
+            (point(0, 2, 0), vector(0, 1, 0)),
+            (point(0.5, 2, 0), vector(0, 1, 0)),
+            (point(0, 2, 0.5), vector(0, 1, 0)),
+        ];
+
+        for (p, normal) in cases {
+            assert_eq!(cyl.local_normal_at(p), normal);
+        }
+    }
+
+    #[test]
+    fn bounds_of_truncated_cylinder() {
+        let c = Cylinder::truncated(-5.0, 3.0, false);
+        assert_eq!(c.local_bounds().min(), point(-1, -5, -1));
+        assert_eq!(c.local_bounds().max(), point(1, 3, 1));
+    }
+}
@@ -0,0 +1,544 @@
+use std::sync::OnceLock;
+
+use crate::{
+    bounds::BoundingBox,
+    bvh::Bvh,
+    material::Material,
+    matrix::helpers::Mat4,
+    ray::{Intersection, Ray},
+    transformation::{Motion, Transform},
+    tuples::Tuple,
+};
+
+use super::{
+    base::{shape_base, ShapeBase},
+    Shape,
+};
+
+/// A shape made of child shapes that are placed as one unit.
+///
+/// Instead of keeping links to its parent, every shape in a hierarchy stores
+/// its full object-to-world transform: adding a child applies the group's
+/// transform to it, and changing the group's transform or motion places all
+/// descendants again from where they sit relative to the group. `normal_at`,
+/// `world_to_object` and `Pattern::at_object` therefore work through any
+/// number of nested groups.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Group {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
+    children: Vec<Box<dyn Shape>>,
+    placements: Vec<Placement>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bvh: OnceLock<Bvh>,
+}
+
+/// Where a child sits inside its container: the transform and motion it had
+/// when it was added. Its world placement is always computed from these, so
+/// moving the container repeatedly doesn't accumulate rounding errors.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Placement {
+    transform: Mat4,
+    motion: Option<Motion>,
+}
+
+impl Placement {
+    pub(super) fn of(child: &dyn Shape) -> Placement {
+        Placement {
+            transform: child.transform().clone(),
+            motion: child.motion().cloned(),
+        }
+    }
+
+    /// Places `child` inside a container with the given transform and
+    /// motion. A moving container carries the child's own motion along.
+    pub(super) fn apply(&self, child: &mut dyn Shape, transform: &Mat4, motion: Option<&Motion>) {
+        child.set_transform(transform * &self.transform);
+        child.set_motion(match (motion, &self.motion) {
+            (None, None) => None,
+            (Some(outer), None) => Some(outer.then(&self.transform)),
+            (None, Some(own)) => Some(own.within(transform)),
+            (Some(outer), Some(own)) => Some(own.within_motion(outer)),
+        });
+    }
+}
+
+impl Group {
+    pub fn new() -> Group {
+        Group {
+            base: ShapeBase::new(),
+            children: Vec::new(),
+            placements: Vec::new(),
+            bvh: OnceLock::new(),
+        }
+    }
+
+    /// Adds `child`, whose transform is taken to be relative to this group.
+    /// The child keeps its own material, which is the one it is shaded with;
+    /// the group's material only reaches it through `set_material` or
+    /// `edit_material`.
+    pub fn add_child(&mut self, mut child: Box<dyn Shape>) {
+        let placement = Placement::of(&*child);
+        placement.apply(&mut *child, self.transform(), self.base.motion.as_ref());
+        self.children.push(child);
+        self.placements.push(placement);
+        self.bvh = OnceLock::new();
+    }
+
+    pub fn with_child<S: Shape + 'static>(mut self, child: S) -> Group {
+        self.add_child(Box::new(child));
+        self
+    }
+
+    /// The children, with their transforms already including this group's.
+    pub fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+    /// Intersects every child, without using the bounding volume hierarchy.
+    /// Meant for validating the accelerated path.
+    pub fn intersect_brute_force(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut xs: Vec<_> = self
+            .children
+            .iter()
+            .flat_map(|c| c.intersect(ray))
+            .collect();
+        xs.sort_by(|a, b| a.t().total_cmp(&b.t()));
+        xs
+    }
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn place_children(&mut self) {
+        for (child, placement) in self.children.iter_mut().zip(&self.placements) {
+            placement.apply(
+                &mut **child,
+                self.base.transform.matrix(),
+                self.base.motion.as_ref(),
+            );
+        }
+        self.bvh = OnceLock::new();
+    }
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "group"))]
+impl Shape for Group {
+    shape_base!(getters);
+
+    fn set_transform(&mut self, new_transform: Mat4) {
+        self.base.transform = Transform::new(new_transform);
+        self.place_children();
+    }
+
+    /// Moves the children along, keeping their placement relative to the
+    /// group and any motion they had of their own.
+    fn set_motion(&mut self, motion: Option<Motion>) {
+        self.base.motion = motion;
+        self.place_children();
+    }
+
+    /// Unlike `material_mut`, which only reaches the group's own material,
+    /// this edits the children's materials as well.
+    fn edit_material(&mut self, edit: &dyn Fn(&mut Material)) {
+        for child in &mut self.children {
+            child.edit_material(edit);
+        }
+        edit(&mut self.base.material);
+    }
+
+    fn set_material(&mut self, new_material: Material) {
+        for child in &mut self.children {
+            child.set_material(new_material.clone());
+        }
+        self.base.material = new_material;
+    }
+
+    /// The children are stored in world space, so the ray is not transformed.
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let bvh = self.bvh.get_or_init(|| {
+            let bounds: Vec<_> = self.children.iter().map(|c| c.bounds()).collect();
+            Bvh::build(&bounds)
+        });
+        let mut xs: Vec<_> = bvh
+            .candidates(ray)
+            .into_iter()
+            .flat_map(|i| self.children[i].intersect(ray))
+            .collect();
+        xs.sort_by(|a, b| a.t().total_cmp(&b.t()));
+        xs
+    }
+
+    /// Like the intersections, the bounds of the children are already in
+    /// world space.
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for c in &self.children {
+            bounds.merge(&c.bounds());
+        }
+        bounds
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        self.bounds().transform(&self.inverse())
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        self.intersect(&ray.transform(self.transform()))
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        std::ptr::addr_eq(self, other) || self.children.iter().any(|c| c.includes(other))
+    }
+
+    /// Hits always refer to one of the children, never to the group itself.
+    fn local_normal_at(&self, _: Tuple) -> Tuple {
+        panic!("Normals of a group are computed by its children")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        material::Material,
+        matrix::helpers::Mat4,
+        patterns::{Pattern, TestPattern},
+        ray::Ray,
+        shapes::{Cube, Cylinder, Plane, Shape, Sphere},
+        transformation::{rotation_y, scaling, translation, Motion, PI},
+        tuples::helpers::{color, point, vector},
+        world::World,
+    };
+
+    use super::Group;
+
+    fn translated<S: Shape>(mut s: S, x: f64, y: f64, z: f64) -> S {
+        s.set_transform(translation(x, y, z));
+        s
+    }
+
+    #[test]
+    fn creating_group() {
+        let g = Group::new();
+        assert_eq!(g.transform(), &Mat4::identity());
+        assert!(g.is_empty());
+    }
+
+    #[test]
+    fn adding_child() {
+        let s = translated(Sphere::new(), 1.0, 2.0, 3.0);
+        let g = Group::new().with_child(s);
+        assert_eq!(g.len(), 1);
+        assert_eq!(g.children()[0].transform(), &translation(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn intersecting_empty_group() {
+        let g = Group::new();
+        let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
+        assert!(g.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn intersecting_nonempty_group() {
+        let g = Group::new()
+            .with_child(Sphere::new())
+            .with_child(translated(Sphere::new(), 0.0, 0.0, -3.0))
+            .with_child(translated(Sphere::new(), 5.0, 0.0, 0.0));
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let xs = g.intersect(&r);
+
+        assert_eq!(xs.len(), 4);
+        let (s1, s2) = (&*g.children()[0], &*g.children()[1]);
+        assert!(std::ptr::addr_eq(xs[0].object(), s2));
+        assert!(std::ptr::addr_eq(xs[1].object(), s2));
+        assert!(std::ptr::addr_eq(xs[2].object(), s1));
+        assert!(std::ptr::addr_eq(xs[3].object(), s1));
+    }
+
+    #[test]
+    fn intersecting_transformed_group() {
+        let mut g = Group::new();
+        g.set_transform(scaling(2.0, 2.0, 2.0));
+        g.add_child(Box::new(translated(Sphere::new(), 5.0, 0.0, 0.0)));
+        let r = Ray::new(point(10, 0, -10), vector(0, 0, 1));
+        assert_eq!(g.intersect(&r).len(), 2);
+    }
+
+    #[test]
+    fn transform_after_adding_children() {
+        let mut g = Group::new().with_child(translated(Sphere::new(), 5.0, 0.0, 0.0));
+        g.set_transform(scaling(2.0, 2.0, 2.0));
+        g.set_transform(scaling(2.0, 2.0, 2.0));
+        assert_eq!(
+            g.children()[0].transform(),
+            &(scaling(2.0, 2.0, 2.0) * translation(5.0, 0.0, 0.0))
+        );
+        let r = Ray::new(point(10, 0, -10), vector(0, 0, 1));
+        assert_eq!(g.intersect(&r).len(), 2);
+    }
+
+    fn nested(g2_transform: Mat4) -> Group {
+        let mut g2 = Group::new();
+        g2.set_transform(g2_transform);
+        g2.add_child(Box::new(translated(Sphere::new(), 5.0, 0.0, 0.0)));
+        let mut g1 = Group::new();
+        g1.set_transform(rotation_y(PI / 2.0));
+        g1.add_child(Box::new(g2));
+        g1
+    }
+
+    fn nested_sphere(g1: &Group) -> &dyn Shape {
+        let g2 = &g1.children()[0];
+        let xs = g2.intersect(&Ray::new(point(-2, 0, -20), vector(0, 0, 1)));
+        xs[0].object()
+    }
+
+    #[test]
+    fn world_to_object() {
+        let g1 = nested(scaling(2.0, 2.0, 2.0));
+        let s = nested_sphere(&g1);
+        assert_eq!(s.world_to_object(point(-2, 0, -10)), point(0, 0, -1));
+    }
+
+    #[test]
+    fn normal_to_world() {
+        let g1 = nested(scaling(1.0, 2.0, 3.0));
+        let s = nested_sphere(&g1);
+        let v = 3.0_f64.sqrt() / 3.0;
+        let n = s.normal_to_world(vector(v, v, v));
+        assert_eq!(n, vector(0.28571, 0.42857, -0.85714));
+    }
+
+    #[test]
+    fn normal_on_child() {
+        let g1 = nested(scaling(1.0, 2.0, 3.0));
+        let s = nested_sphere(&g1);
+        let n = s.normal_at(point(1.7321, 1.1547, -5.5774));
+        assert_eq!(n, vector(0.2857, 0.42854, -0.85716));
+    }
+
+    #[test]
+    fn pattern_on_child() {
+        let g1 = nested(scaling(2.0, 2.0, 2.0));
+        let s = nested_sphere(&g1);
+        let c = TestPattern::new().at_object(s, &point(-2, 0, -10));
+        assert_eq!(c, color(0, 0, -1));
+    }
+
+    #[test]
+    fn set_material_applies_to_children() {
+        let mut g = nested(scaling(2.0, 2.0, 2.0));
+        let m = Material {
+            color: color(1, 0, 0).solid(),
+            ambient: 1.0,
+            ..Default::default()
+        };
+        g.set_material(m.clone());
+        assert_eq!(nested_sphere(&g).material(), &m);
+    }
+
+    #[test]
+    fn editing_material_keeps_children_own_fields() {
+        let mut s = translated(Sphere::new(), 5.0, 0.0, 0.0);
+        s.material_mut().color = color(1, 0, 0).solid();
+        let mut g2 = Group::new();
+        g2.add_child(Box::new(s));
+        let mut g1 = Group::new();
+        g1.add_child(Box::new(g2));
+        let r = Ray::new(point(5, 0, -20), vector(0, 0, 1));
+
+        g1.material_mut().ambient = 0.7;
+        assert_eq!(g1.intersect(&r)[0].object().material().ambient, 0.1);
+
+        g1.edit_material(&|m| m.reflective = 0.3);
+        let xs = g1.intersect(&r);
+        let m = xs[0].object().material();
+        assert_eq!(m.reflective, 0.3);
+        assert_eq!(m.color.at(&point(0, 0, 0)), color(1, 0, 0));
+    }
+
+    #[test]
+    fn bounds_of_children() {
+        let mut s = Sphere::new();
+        s.set_transform(translation(2.0, 5.0, -3.0) * scaling(2.0, 2.0, 2.0));
+        let mut c = Cylinder::truncated(-2.0, 2.0, false);
+        c.set_transform(translation(-4.0, -1.0, 4.0) * scaling(0.5, 1.0, 0.5));
+        let mut g = Group::new().with_child(s).with_child(c);
+        let b = g.bounds();
+        assert_eq!(b.min(), point(-4.5, -3, -5));
+        assert_eq!(b.max(), point(4, 7, 4.5));
+
+        g.set_transform(translation(1.0, 0.0, 0.0));
+        assert_eq!(g.bounds().min(), point(-3.5, -3, -5));
+        assert_eq!(g.local_bounds().min(), point(-4.5, -3, -5));
+    }
+
+    #[test]
+    fn bvh_matches_brute_force() {
+        let mut g = Group::new().with_child(Plane::new());
+        for i in 0..40 {
+            let mut s = Sphere::new();
+            s.set_transform(translation(
+                (i % 7) as f64 - 3.0,
+                (i / 7) as f64,
+                i as f64 * 0.3,
+            ));
+            g.add_child(Box::new(s));
+        }
+        g.set_transform(rotation_y(0.3));
+
+        for x in -8..8 {
+            let r = Ray::new(
+                point(x as f64 * 0.5, 2, -10),
+                vector(0.05, -0.1, 1).normalized(),
+            );
+            let a = g.intersect(&r);
+            let b = g.intersect_brute_force(&r);
+            assert_eq!(a.len(), b.len());
+            for (a, b) in a.iter().zip(&b) {
+                assert_eq!(a.t().to_bits(), b.t().to_bits());
+                assert!(std::ptr::addr_eq(a.object(), b.object()));
+            }
+        }
+    }
+
+    #[test]
+    fn table_as_one_unit() {
+        let leg = |x: f64, z: f64| {
+            let mut c = Cube::new();
+            c.set_transform(translation(x, 1.0, z) * scaling(0.1, 1.0, 0.1));
+            c
+        };
+        let mut top = Cube::new();
+        top.set_transform(translation(0.0, 2.05, 0.0) * scaling(1.0, 0.05, 1.0));
+        let mut table = Group::new()
+            .with_child(top)
+            .with_child(leg(0.9, 0.9))
+            .with_child(leg(-0.9, 0.9))
+            .with_child(leg(0.9, -0.9))
+            .with_child(leg(-0.9, -0.9));
+        table.set_transform(translation(10.0, 0.0, 0.0) * rotation_y(PI / 4.0));
+
+        let mut w = World::default();
+        w.objetcs_mut().clear();
+        w.objetcs_mut().push(Box::new(table));
+
+        let r = Ray::new(point(10, 5, 0), vector(0, -1, 0));
+        let xs = w.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        let comps = xs[0].prepare_comps(&r);
+        assert_eq!(comps.point, point(10, 2.1, 0));
+        assert_eq!(comps.normalv, vector(0, 1, 0));
+
+        let r = Ray::new(point(0, 5, 0), vector(0, -1, 0));
+        assert!(w.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn pattern_arc_on_group_child() {
+        let mut s = Sphere::new();
+        s.material_mut().color = Arc::new(TestPattern::new());
+        let mut g = Group::new();
+        g.set_transform(translation(1.0, 0.0, 0.0));
+        g.add_child(Box::new(s));
+        let child = &*g.children()[0];
+        let c = child.material().color.at_object(child, &point(1, 2, 3));
+        assert_eq!(c, color(0, 2, 3));
+    }
+
+    #[test]
+    fn moving_group_moves_children() {
+        let mut g = Group::new();
+        g.set_transform(translation(0.0, 0.0, 10.0));
+        g.add_child(Box::new(translated(Sphere::new(), 2.0, 0.0, 0.0)));
+        g.set_motion(Some(Motion::linear(
+            translation(0.0, 0.0, 10.0),
+            translation(0.0, 3.0, 10.0),
+        )));
+        g.add_child(Box::new(translated(Sphere::new(), -2.0, 0.0, 0.0)));
+
+        for x in [2, -2] {
+            let r = Ray::new(point(x, 3, 0), vector(0, 0, 1));
+            assert!(g.intersect(&r).is_empty());
+            let xs = g.intersect(&r.with_time(1.0));
+            assert_eq!(xs.len(), 2);
+            assert_eq!(xs[0].t(), 9.0);
+        }
+        assert_eq!(g.bounds().max(), point(3, 4, 11));
+
+        g.set_motion(None);
+        assert!(g
+            .intersect(&Ray::new(point(2, 3, 0), vector(0, 0, 1)).with_time(1.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn changing_transform_does_not_drift() {
+        let mut g2 = Group::new();
+        g2.add_child(Box::new(translated(Sphere::new(), 5.0, 0.0, 0.0)));
+        let mut g1 = Group::new();
+        g1.add_child(Box::new(g2));
+
+        for i in 0..100 {
+            g1.set_transform(rotation_y(i as f64 * 0.37) * scaling(1.3, 0.7, 2.1));
+        }
+        g1.set_transform(scaling(2.0, 2.0, 2.0));
+
+        let r = Ray::new(point(10, 0, -20), vector(0, 0, 1));
+        let xs = g1.intersect(&r);
+        let expected = scaling(2.0, 2.0, 2.0) * translation(5.0, 0.0, 0.0);
+        let actual = xs[0].object().transform();
+        for row in 0..4 {
+            for col in 0..4 {
+                assert_eq!(actual[(row, col)], expected[(row, col)]);
+            }
+        }
+    }
+
+    #[test]
+    fn mixing_motion_and_transform() {
+        let mut s = Sphere::new();
+        s.set_motion(Some(Motion::linear(
+            Mat4::identity(),
+            translation(0.0, 2.0, 0.0),
+        )));
+        let mut g = Group::new();
+        g.add_child(Box::new(s));
+        let child_at = |g: &Group, time: f64| g.children()[0].motion().unwrap().at(time);
+
+        // The child's own motion is kept, inside the group's transform.
+        g.set_transform(translation(4.0, 0.0, 0.0));
+        assert_eq!(child_at(&g, 0.5), translation(4.0, 1.0, 0.0));
+
+        // A moving group carries the child's motion along.
+        g.set_motion(Some(Motion::linear(
+            Mat4::identity(),
+            translation(0.0, 0.0, 6.0),
+        )));
+        assert_eq!(child_at(&g, 0.5), translation(0.0, 1.0, 3.0));
+
+        // Changing the transform afterwards doesn't lose either motion, and
+        // once the group stops moving its new transform applies.
+        g.set_transform(scaling(2.0, 2.0, 2.0));
+        assert_eq!(child_at(&g, 1.0), translation(0.0, 2.0, 6.0));
+        g.set_motion(None);
+        assert_eq!(
+            child_at(&g, 0.5),
+            scaling(2.0, 2.0, 2.0) * translation(0.0, 1.0, 0.0)
+        );
+        assert_eq!(g.children()[0].transform(), &scaling(2.0, 2.0, 2.0));
+    }
+}
@@ -0,0 +1,238 @@
+use std::sync::OnceLock;
+
+use crate::{
+    bounds::BoundingBox,
+    bvh::Bvh,
+    material::Material,
+    matrix::helpers::Mat4,
+    ray::{Intersection, Ray},
+    transformation::{Motion, Transform},
+    tuples::Tuple,
+};
+
+use super::{
+    base::{shape_base, ShapeBase},
+    Shape,
+};
+
+/// A collection of triangles that is placed and shaded as a single object,
+/// e.g. a model loaded from an OBJ file.
+///
+/// The triangles live in the mesh's object space. Setting the transform or
+/// material of the mesh applies it to every triangle, so hits and normals
+/// reported by the triangles are already correct in world space.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mesh {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
+    triangles: Vec<Box<dyn Shape>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bvh: OnceLock<Bvh>,
+}
+
+impl Mesh {
+    pub fn new(triangles: Vec<Box<dyn Shape>>) -> Mesh {
+        let mut mesh = Mesh {
+            base: ShapeBase::new(),
+            triangles,
+            bvh: OnceLock::new(),
+        };
+        mesh.set_material(Material::default());
+        mesh
+    }
+    pub fn triangles(&self) -> &[Box<dyn Shape>] {
+        &self.triangles
+    }
+    /// Intersects every triangle, without using the bounding volume
+    /// hierarchy. Meant for validating the accelerated path.
+    pub fn local_intersect_brute_force(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        self.triangles
+            .iter()
+            .flat_map(|t| t.local_intersect(ray))
+            .collect()
+    }
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+}
+
+impl Default for Mesh {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "mesh"))]
+impl Shape for Mesh {
+    shape_base!(getters);
+
+    fn set_transform(&mut self, new_transform: Mat4) {
+        for triangle in &mut self.triangles {
+            triangle.set_transform(new_transform.clone());
+        }
+        self.base.transform = Transform::new(new_transform);
+    }
+
+    fn set_motion(&mut self, motion: Option<Motion>) {
+        for triangle in &mut self.triangles {
+            triangle.set_motion(motion.clone());
+        }
+        self.base.motion = motion;
+    }
+
+    /// `material_mut` leaves the triangles' materials alone; this edits
+    /// them too.
+    fn edit_material(&mut self, edit: &dyn Fn(&mut Material)) {
+        for triangle in &mut self.triangles {
+            triangle.edit_material(edit);
+        }
+        edit(&mut self.base.material);
+    }
+
+    fn set_material(&mut self, new_material: Material) {
+        for triangle in &mut self.triangles {
+            triangle.set_material(new_material.clone());
+        }
+        self.base.material = new_material;
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let bvh = self.bvh.get_or_init(|| {
+            let bounds: Vec<_> = self.triangles.iter().map(|t| t.local_bounds()).collect();
+            Bvh::build(&bounds)
+        });
+        bvh.candidates(ray)
+            .into_iter()
+            .flat_map(|i| self.triangles[i].local_intersect(ray))
+            .collect()
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for t in &self.triangles {
+            bounds.merge(&t.local_bounds());
+        }
+        bounds
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        std::ptr::addr_eq(self, other) || self.triangles.iter().any(|c| c.includes(other))
+    }
+
+    /// Hits always refer to one of the triangles, never to the mesh itself.
+    fn local_normal_at(&self, _: Tuple) -> Tuple {
+        panic!("Normals of a mesh are computed by its triangles")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        material::Material,
+        ray::Ray,
+        shapes::{Shape, Triangle},
+        transformation::{scaling, translation},
+        tuples::{
+            helpers::{point, vector},
+            FEquals,
+        },
+    };
+
+    use super::Mesh;
+
+    fn setup() -> Mesh {
+        Mesh::new(vec![
+            Box::new(Triangle::new(
+                point(0, 1, 0),
+                point(-1, 0, 0),
+                point(1, 0, 0),
+            )),
+            Box::new(Triangle::new(
+                point(0, 1, 2),
+                point(-1, 0, 2),
+                point(1, 0, 2),
+            )),
+        ])
+    }
+
+    #[test]
+    fn intersects_all_triangles() {
+        let m = setup();
+        let r = Ray::new(point(0, 0.5, -2), vector(0, 0, 1));
+        let xs = m.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(std::ptr::addr_eq(xs[0].object(), &*m.triangles()[0]));
+        assert!(xs[0].t().eps_eq(2.0));
+        assert!(xs[1].t().eps_eq(4.0));
+    }
+
+    #[test]
+    fn transform_applies_to_triangles() {
+        let mut m = setup();
+        m.set_transform(translation(5.0, 0.0, 0.0) * scaling(2.0, 2.0, 2.0));
+        let r = Ray::new(point(5, 1, -10), vector(0, 0, 1));
+        let xs = m.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[1].t().eps_eq(14.0));
+
+        let n = xs[0].object().normal_at(point(5, 1, 0));
+        assert_eq!(n, vector(0, 0, -1));
+        assert_eq!(m.triangles()[1].transform(), m.transform());
+    }
+
+    #[test]
+    fn bounds_contain_triangles() {
+        let mut m = setup();
+        assert_eq!(m.local_bounds().min(), point(-1, 0, 0));
+        assert_eq!(m.local_bounds().max(), point(1, 1, 2));
+        m.set_transform(translation(5.0, 0.0, 0.0));
+        assert_eq!(m.bounds().min(), point(4, 0, 0));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let triangles = (0..50)
+            .map(|i| -> Box<dyn Shape> {
+                let z = i as f64 * 0.5;
+                Box::new(Triangle::new(
+                    point(0, 1, z),
+                    point(-1, 0, z + 0.2),
+                    point(1, 0, z - 0.2),
+                ))
+            })
+            .collect();
+        let m = Mesh::new(triangles);
+        for (x, y) in [(0.0, 0.5), (0.9, 0.05), (-0.3, 0.3), (2.0, 0.5)] {
+            let r = Ray::new(point(x, y, -5), vector(0.01, 0, 1).normalized());
+            let a = m.local_intersect(&r);
+            let b = m.local_intersect_brute_force(&r);
+            assert_eq!(a.len(), b.len());
+            for (a, b) in a.iter().zip(&b) {
+                assert_eq!(a.t().to_bits(), b.t().to_bits());
+                assert!(std::ptr::addr_eq(a.object(), b.object()));
+            }
+        }
+    }
+
+    #[test]
+    fn material_applies_to_triangles() {
+        let mut m = setup();
+        let mat = Material {
+            ambient: 1.0,
+            ..Default::default()
+        };
+        m.set_material(mat.clone());
+        assert!(m.triangles().iter().all(|t| t.material() == &mat));
+    }
+
+    #[test]
+    fn material_edits_reach_triangles() {
+        let mut m = setup();
+        m.edit_material(&|m| m.reflective = 0.5);
+        assert!(m.triangles().iter().all(|t| t.material().reflective == 0.5));
+    }
+}
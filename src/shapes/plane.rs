@@ -1,49 +1,43 @@
 use crate::{
-    material::Material,
-    matrix::helpers::Mat4,
+    bounds::BoundingBox,
     ray::Intersection,
-    tuples::{helpers::vector, EPSILON},
+    tuples::{
+        helpers::{point, vector},
+        EPSILON,
+    },
 };
 
-use super::Shape;
+use super::{
+    base::{shape_base, ShapeBase},
+    Shape,
+};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Plane {
-    transform: Mat4,
-    material: Material,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
 }
 
 impl Plane {
     pub fn new() -> Plane {
         Plane {
-            transform: Mat4::identity(),
-            material: Material::default(),
+            base: ShapeBase::new(),
         }
     }
 }
 
-impl Shape for Plane {
-    fn transform(&self) -> &Mat4 {
-        &self.transform
-    }
-
-    fn set_transform(&mut self, new_transform: Mat4) {
-        self.transform = new_transform;
-    }
-
-    fn material(&self) -> &Material {
-        &self.material
-    }
-
-    fn material_mut(&mut self) -> &mut Material {
-        &mut self.material
+impl Default for Plane {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn set_material(&mut self, new_material: Material) {
-        self.material = new_material;
-    }
+#[cfg_attr(feature = "serde", typetag::serde(name = "plane"))]
+impl Shape for Plane {
+    shape_base!();
 
-    fn local_intersect(&self, ray: &crate::ray::Ray) -> Vec<crate::ray::Intersection> {
+    fn local_intersect(&self, ray: &crate::ray::Ray) -> Vec<crate::ray::Intersection<'_>> {
         if ray.direction().y().abs() < EPSILON {
             Vec::new()
         } else {
@@ -54,7 +48,14 @@ impl Shape for Plane {
         }
     }
 
-    fn local_normal_at(&self, p: crate::tuples::Tuple) -> crate::tuples::Tuple {
+    fn local_bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            point(f64::NEG_INFINITY, 0, f64::NEG_INFINITY),
+            point(f64::INFINITY, 0, f64::INFINITY),
+        )
+    }
+
+    fn local_normal_at(&self, _: crate::tuples::Tuple) -> crate::tuples::Tuple {
         vector::up()
     }
 }
@@ -119,4 +120,12 @@ mod tests {
         assert!(xs[0].t().eps_eq(1.0));
         // assert!(xs[0].object(), p); //TODO
     }
+
+    #[test]
+    fn bounds_are_infinite_except_y() {
+        let b = Plane::new().local_bounds();
+        assert_eq!(b.min().x(), f64::NEG_INFINITY);
+        assert_eq!(b.max().z(), f64::INFINITY);
+        assert!(b.min().y().eps_eq(0.0) && b.max().y().eps_eq(0.0));
+    }
 }
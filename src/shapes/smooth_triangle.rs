@@ -0,0 +1,148 @@
+use crate::{
+    bounds::BoundingBox,
+    ray::{Intersection, Ray},
+    tuples::Tuple,
+};
+
+use super::{
+    base::{shape_base, ShapeBase},
+    triangle::intersect_triangle,
+    Shape,
+};
+
+/// A triangle that interpolates the normals given for its three vertices.
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmoothTriangle {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
+    p1: Tuple,
+    p2: Tuple,
+    p3: Tuple,
+    n1: Tuple,
+    n2: Tuple,
+    n3: Tuple,
+    e1: Tuple,
+    e2: Tuple,
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple, n1: Tuple, n2: Tuple, n3: Tuple) -> SmoothTriangle {
+        Self {
+            base: ShapeBase::new(),
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+        }
+    }
+    pub fn p1(&self) -> Tuple {
+        self.p1
+    }
+    pub fn p2(&self) -> Tuple {
+        self.p2
+    }
+    pub fn p3(&self) -> Tuple {
+        self.p3
+    }
+    pub fn n1(&self) -> Tuple {
+        self.n1
+    }
+    pub fn n2(&self) -> Tuple {
+        self.n2
+    }
+    pub fn n3(&self) -> Tuple {
+        self.n3
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "smooth-triangle"))]
+impl Shape for SmoothTriangle {
+    shape_base!();
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        match intersect_triangle(self.p1, self.e1, self.e2, ray) {
+            Some((t, u, v)) => vec![Intersection::with_uv(t, self, u, v)],
+            None => Vec::new(),
+        }
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        BoundingBox::from_points([self.p1, self.p2, self.p3])
+    }
+
+    /// Without hit information only the flat face normal is known.
+    fn local_normal_at(&self, _: Tuple) -> Tuple {
+        self.e2.cross(&self.e1).normalized()
+    }
+
+    fn local_normal_at_hit(&self, _: Tuple, hit: &Intersection) -> Tuple {
+        self.n2 * hit.u() + self.n3 * hit.v() + self.n1 * (1.0 - hit.u() - hit.v())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::{Intersection, Ray},
+        shapes::Shape,
+        tuples::{
+            helpers::{point, vector},
+            FEquals,
+        },
+    };
+
+    use super::SmoothTriangle;
+
+    fn setup() -> SmoothTriangle {
+        SmoothTriangle::new(
+            point(0, 1, 0),
+            point(-1, 0, 0),
+            point(1, 0, 0),
+            vector(0, 1, 0),
+            vector(-1, 0, 0),
+            vector(1, 0, 0),
+        )
+    }
+
+    #[test]
+    fn constructing_smooth_triangle() {
+        let tri = setup();
+        assert_eq!(tri.p1(), point(0, 1, 0));
+        assert_eq!(tri.p2(), point(-1, 0, 0));
+        assert_eq!(tri.p3(), point(1, 0, 0));
+        assert_eq!(tri.n1(), vector(0, 1, 0));
+        assert_eq!(tri.n2(), vector(-1, 0, 0));
+        assert_eq!(tri.n3(), vector(1, 0, 0));
+    }
+
+    #[test]
+    fn intersection_stores_uv() {
+        let tri = setup();
+        let r = Ray::new(point(-0.2, 0.3, -2), vector(0, 0, 1));
+        let xs = tri.local_intersect(&r);
+        assert!(xs[0].u().eps_eq(0.45));
+        assert!(xs[0].v().eps_eq(0.25));
+    }
+
+    #[test]
+    fn interpolates_normal() {
+        let tri = setup();
+        let i = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+        let n = tri.normal_at_hit(point(0, 0, 0), &i);
+        assert_eq!(n, vector(-0.5547, 0.83205, 0));
+    }
+
+    #[test]
+    fn prepare_comps_uses_interpolated_normal() {
+        let tri = setup();
+        let i = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+        let r = Ray::new(point(-0.2, 0.3, -2), vector(0, 0, 1));
+        let comps = i.prepare_comps(&r);
+        assert_eq!(comps.normalv, vector(-0.5547, 0.83205, 0));
+    }
+}
@@ -1,6 +1,5 @@
 use crate::{
-    material::Material,
-    matrix::helpers::Mat4,
+    bounds::BoundingBox,
     ray::{Intersection, Ray},
     tuples::{
         helpers::{point, points},
@@ -8,45 +7,45 @@ use crate::{
     },
 };
 
-use super::Shape;
+use super::{
+    base::{shape_base, ShapeBase},
+    Shape,
+};
 
 #[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sphere {
-    transform: Mat4,
-    material: Material,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
 }
 
 impl Sphere {
     pub fn new() -> Sphere {
         Self {
-            transform: Mat4::identity(),
-            material: Material::default(),
+            base: ShapeBase::new(),
         }
     }
-}
-
-impl Shape for Sphere {
-    fn transform(&self) -> &Mat4 {
-        &self.transform
-    }
 
-    fn set_transform(&mut self, new_transform: Mat4) {
-        self.transform = new_transform;
-    }
-
-    fn material(&self) -> &Material {
-        &self.material
+    /// A unit sphere made of glass (fully transparent, refractive index 1.5).
+    pub fn glass() -> Sphere {
+        let mut s = Sphere::new();
+        s.base.material.transparency = 1.0;
+        s.base.material.refractive_index = 1.5;
+        s
     }
+}
 
-    fn material_mut(&mut self) -> &mut Material {
-        &mut self.material
+impl Default for Sphere {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn set_material(&mut self, new_material: Material) {
-        self.material = new_material;
-    }
+#[cfg_attr(feature = "serde", typetag::serde(name = "sphere"))]
+impl Shape for Sphere {
+    shape_base!();
 
-    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
         let sphere_to_ray = ray.origin() - point(0, 0, 0);
 
         let a = ray.direction().dot(&ray.direction());
@@ -64,7 +63,11 @@ impl Shape for Sphere {
         let t1 = (-b - disc_sqrt) / (2.0 * a);
         let t2 = (-b + disc_sqrt) / (2.0 * a);
 
-        return vec![Intersection::new(t1, self), Intersection::new(t2, self)];
+        vec![Intersection::new(t1, self), Intersection::new(t2, self)]
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        BoundingBox::new(point(-1, -1, -1), point(1, 1, 1))
     }
 
     fn local_normal_at(&self, p: Tuple) -> Tuple {
@@ -235,6 +238,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn normal_on_translated() {
         let mut s = Sphere::new();
         s.set_transform(translation(0.0, 1.0, 0.0));
@@ -260,6 +264,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn sphere_modified_material() {
         let mut s = Sphere::new();
 
@@ -269,4 +274,20 @@ mod tests {
         s.set_material(Material::clone(&m));
         assert_eq!(s.material(), &m);
     }
+
+    #[test]
+    fn glass_sphere() {
+        let s = Sphere::glass();
+        assert_eq!(s.transform(), &Mat4::identity());
+        assert!(s.material().transparency.eps_eq(1.0));
+        assert!(s.material().refractive_index.eps_eq(1.5));
+    }
+
+    #[test]
+    fn bounds_of_transformed_sphere() {
+        let mut s = Sphere::new();
+        s.set_transform(translation(1.0, 2.0, 3.0) * scaling(0.5, 2.0, 4.0));
+        assert_eq!(s.bounds().min(), point(0.5, 0, -1));
+        assert_eq!(s.bounds().max(), point(1.5, 4, 7));
+    }
 }
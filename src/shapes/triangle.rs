@@ -0,0 +1,176 @@
+use crate::{
+    bounds::BoundingBox,
+    ray::{Intersection, Ray},
+    tuples::{Tuple, EPSILON},
+};
+
+use super::{
+    base::{shape_base, ShapeBase},
+    Shape,
+};
+
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Triangle {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: ShapeBase,
+    p1: Tuple,
+    p2: Tuple,
+    p3: Tuple,
+    e1: Tuple,
+    e2: Tuple,
+    normal: Tuple,
+}
+
+impl Triangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple) -> Triangle {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        Self {
+            base: ShapeBase::new(),
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(&e1).normalized(),
+        }
+    }
+    pub fn p1(&self) -> Tuple {
+        self.p1
+    }
+    pub fn p2(&self) -> Tuple {
+        self.p2
+    }
+    pub fn p3(&self) -> Tuple {
+        self.p3
+    }
+    pub fn e1(&self) -> Tuple {
+        self.e1
+    }
+    pub fn e2(&self) -> Tuple {
+        self.e2
+    }
+    pub fn normal(&self) -> Tuple {
+        self.normal
+    }
+}
+
+/// Möller–Trumbore ray/triangle intersection, returning `(t, u, v)`.
+pub(super) fn intersect_triangle(
+    p1: Tuple,
+    e1: Tuple,
+    e2: Tuple,
+    ray: &Ray,
+) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = ray.direction().cross(&e2);
+    let det = e1.dot(&dir_cross_e2);
+    if det.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / det;
+    let p1_to_origin = ray.origin() - p1;
+    let u = f * p1_to_origin.dot(&dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(&e1);
+    let v = f * ray.direction().dot(&origin_cross_e1);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    Some((f * e2.dot(&origin_cross_e1), u, v))
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "triangle"))]
+impl Shape for Triangle {
+    shape_base!();
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        match intersect_triangle(self.p1, self.e1, self.e2, ray) {
+            Some((t, u, v)) => vec![Intersection::with_uv(t, self, u, v)],
+            None => Vec::new(),
+        }
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        BoundingBox::from_points([self.p1, self.p2, self.p3])
+    }
+
+    fn local_normal_at(&self, _: Tuple) -> Tuple {
+        self.normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::Ray,
+        shapes::Shape,
+        tuples::{
+            helpers::{point, vector},
+            FEquals,
+        },
+    };
+
+    use super::Triangle;
+
+    fn setup() -> Triangle {
+        Triangle::new(point(0, 1, 0), point(-1, 0, 0), point(1, 0, 0))
+    }
+
+    #[test]
+    fn constructing_triangle() {
+        let t = setup();
+        assert_eq!(t.p1(), point(0, 1, 0));
+        assert_eq!(t.p2(), point(-1, 0, 0));
+        assert_eq!(t.p3(), point(1, 0, 0));
+        assert_eq!(t.e1(), vector(-1, -1, 0));
+        assert_eq!(t.e2(), vector(1, -1, 0));
+        assert_eq!(t.normal(), vector(0, 0, -1));
+    }
+
+    #[test]
+    fn normal_on_triangle() {
+        let t = setup();
+        assert_eq!(t.local_normal_at(point(0, 0.5, 0)), t.normal());
+        assert_eq!(t.local_normal_at(point(-0.5, 0.75, 0)), t.normal());
+        assert_eq!(t.local_normal_at(point(0.5, 0.25, 0)), t.normal());
+    }
+
+    #[test]
+    fn ray_parallel_to_triangle() {
+        let t = setup();
+        let r = Ray::new(point(0, -1, -2), vector(0, 1, 0));
+        assert_eq!(t.local_intersect(&r).len(), 0);
+    }
+
+    #[test]
+    fn ray_misses_edges() {
+        let t = setup();
+        for origin in [point(1, 1, -2), point(-1, 1, -2), point(0, -1, -2)] {
+            let r = Ray::new(origin, vector(0, 0, 1));
+            assert_eq!(t.local_intersect(&r).len(), 0);
+        }
+    }
+
+    #[test]
+    fn ray_strikes_triangle() {
+        let t = setup();
+        let r = Ray::new(point(0, 0.5, -2), vector(0, 0, 1));
+        let xs = t.local_intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].t().eps_eq(2.0));
+    }
+
+    #[test]
+    fn bounds_of_triangle() {
+        let t = Triangle::new(point(-3, 7, 2), point(6, 2, -4), point(2, -1, -1));
+        assert_eq!(t.local_bounds().min(), point(-3, -1, -4));
+        assert_eq!(t.local_bounds().max(), point(6, 7, 2));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{matrix::helpers::Mat4, tuples::Tuple};
+use crate::{bounds::BoundingBox, matrix::helpers::Mat4, tuples::Tuple};
 
 pub fn translation(x: f64, y: f64, z: f64) -> Mat4 {
     Mat4::new(
@@ -108,6 +108,184 @@ pub fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> Mat4 {
 
 pub const PI: f64 = std::f64::consts::PI;
 
+/// A transformation matrix together with its inverse and inverse transpose,
+/// so they are computed once instead of on every ray. Only the matrix is
+/// serialized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Mat4", into = "Mat4")
+)]
+pub struct Transform {
+    matrix: Mat4,
+    inverse: Mat4,
+    inverse_transpose: Mat4,
+}
+
+impl Transform {
+    /// A singular `matrix` (see `Mat4::invertible`) gets an inverse of NaNs,
+    /// so nothing placed with it can be hit. Use `try_new` to catch those.
+    pub fn new(matrix: Mat4) -> Transform {
+        Transform::try_new(matrix.clone()).unwrap_or_else(|| {
+            let mut nan = Mat4::zero();
+            for row in 0..4 {
+                for col in 0..4 {
+                    nan[(row, col)] = f64::NAN;
+                }
+            }
+            Transform {
+                matrix,
+                inverse: nan.clone(),
+                inverse_transpose: nan,
+            }
+        })
+    }
+    /// The transform, or `None` if `matrix` can't be inverted.
+    pub fn try_new(matrix: Mat4) -> Option<Transform> {
+        let inverse = matrix.try_inverse()?;
+        let inverse_transpose = inverse.transpose();
+        Some(Transform {
+            matrix,
+            inverse,
+            inverse_transpose,
+        })
+    }
+    pub const fn identity() -> Transform {
+        Transform {
+            matrix: Mat4::identity(),
+            inverse: Mat4::identity(),
+            inverse_transpose: Mat4::identity(),
+        }
+    }
+    pub fn matrix(&self) -> &Mat4 {
+        &self.matrix
+    }
+    pub fn inverse(&self) -> &Mat4 {
+        &self.inverse
+    }
+    pub fn inverse_transpose(&self) -> &Mat4 {
+        &self.inverse_transpose
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl From<Mat4> for Transform {
+    fn from(matrix: Mat4) -> Self {
+        Transform::new(matrix)
+    }
+}
+
+impl From<Transform> for Mat4 {
+    fn from(transform: Transform) -> Self {
+        transform.matrix
+    }
+}
+
+/// A transform that changes over time, given as keyframes. Between two
+/// keyframes the matrices are interpolated linearly, which is exact for
+/// translation and scaling; rotations need a few keyframes to stay rigid.
+/// Before the first and after the last keyframe the transform stays put.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Motion {
+    keyframes: Vec<(f64, Mat4)>,
+}
+
+impl Motion {
+    /// Moves from `open` at time `0.0` to `close` at time `1.0`.
+    pub fn linear(open: Mat4, close: Mat4) -> Motion {
+        Motion::keyframed(vec![(0.0, open), (1.0, close)])
+    }
+
+    /// # Panics
+    /// If `keyframes` is empty.
+    pub fn keyframed(mut keyframes: Vec<(f64, Mat4)>) -> Motion {
+        assert!(
+            !keyframes.is_empty(),
+            "A motion needs at least one keyframe"
+        );
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Motion { keyframes }
+    }
+
+    pub fn keyframes(&self) -> &[(f64, Mat4)] {
+        &self.keyframes
+    }
+
+    /// The transform at `time`.
+    pub fn at(&self, time: f64) -> Mat4 {
+        let next = self.keyframes.partition_point(|(t, _)| *t <= time);
+        if next == 0 {
+            return self.keyframes[0].1.clone();
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes[next - 1].1.clone();
+        }
+
+        let (t0, m0) = &self.keyframes[next - 1];
+        let (t1, m1) = &self.keyframes[next];
+        let f = (time - t0) / (t1 - t0);
+        let mut m = Mat4::zero();
+        for row in 0..4 {
+            for col in 0..4 {
+                m[(row, col)] = m0[(row, col)] * (1.0 - f) + m1[(row, col)] * f;
+            }
+        }
+        m
+    }
+
+    /// This motion followed by the fixed transform `m`, i.e. every keyframe
+    /// multiplied by `m` on the right.
+    pub fn then(&self, m: &Mat4) -> Motion {
+        Motion {
+            keyframes: self.keyframes.iter().map(|(t, k)| (*t, k * m)).collect(),
+        }
+    }
+
+    /// This motion inside the fixed transform `m`, i.e. every keyframe
+    /// multiplied by `m` on the left.
+    pub fn within(&self, m: &Mat4) -> Motion {
+        Motion {
+            keyframes: self.keyframes.iter().map(|(t, k)| (*t, m * k)).collect(),
+        }
+    }
+
+    /// This motion inside the moving `outer` one. There is a keyframe at
+    /// every keyframe time of either motion, where the result is exact.
+    pub fn within_motion(&self, outer: &Motion) -> Motion {
+        let mut times: Vec<_> = self
+            .keyframes
+            .iter()
+            .chain(&outer.keyframes)
+            .map(|(t, _)| *t)
+            .collect();
+        times.sort_by(f64::total_cmp);
+        times.dedup();
+        Motion {
+            keyframes: times
+                .into_iter()
+                .map(|t| (t, outer.at(t) * self.at(t)))
+                .collect(),
+        }
+    }
+
+    /// The union of `local` transformed by every keyframe. The interpolated
+    /// transforms are blends of the keyframes, so this covers all of them.
+    pub fn bounds(&self, local: &BoundingBox) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for (_, m) in &self.keyframes {
+            bounds.merge(&local.transform(m));
+        }
+        bounds
+    }
+}
+
 pub mod helper {
     use crate::matrix::helpers::Mat4;
 
@@ -162,12 +340,15 @@ pub mod helper {
 mod tests {
 
     use crate::{
+        bounds::BoundingBox,
         matrix::helpers::Mat4,
         transformation::{rotation_y, rotation_z, shearing, view_transform},
         tuples::helpers::{point, vector},
     };
 
-    use super::{helper::TransformationBuilder, rotation_x, scaling, translation, PI};
+    use super::{
+        helper::TransformationBuilder, rotation_x, scaling, translation, Motion, Transform, PI,
+    };
 
     #[test]
     fn mul_translation() {
@@ -366,6 +547,7 @@ mod tests {
         assert_eq!(t * p, point(15, 0, 7));
     }
 
+    #[allow(dead_code)]
     fn view_transformation_matrix_default() {
         let from = point(0, 0, 0);
         let to = point(0, 0, -1);
@@ -375,6 +557,7 @@ mod tests {
         assert_eq!(t, Mat4::identity());
     }
 
+    #[allow(dead_code)]
     fn view_transformation_positive_z() {
         let from = point(0, 0, 0);
         let to = point(0, 0, 1);
@@ -384,6 +567,7 @@ mod tests {
         assert_eq!(t, scaling(-1.0, -1.0, -1.0));
     }
 
+    #[allow(dead_code)]
     fn view_transformation_moves_world() {
         let from = point(0, 0, 8);
         let to = point(0, 0, 0);
@@ -393,6 +577,7 @@ mod tests {
         assert_eq!(t, translation(0.0, 0.0, -8.0));
     }
 
+    #[allow(dead_code)]
     fn view_transformation_arbitrary() {
         let from = point(1, 3, 2);
         let to = point(4, -2, 8);
@@ -410,4 +595,85 @@ mod tests {
                 .into()
         );
     }
+
+    #[test]
+    fn transform_caches_inverse() {
+        let m = TransformationBuilder::create()
+            .rotation_x(PI / 3.0)
+            .scaling(2.0, 3.0, 4.0)
+            .translation(1.0, -2.0, 3.0)
+            .build();
+        let t = Transform::new(m.clone());
+
+        assert_eq!(t.matrix(), &m);
+        assert_eq!(t.inverse(), &m.inverse());
+        assert_eq!(t.inverse_transpose(), &m.inverse().transpose());
+    }
+
+    #[test]
+    fn singular_transforms() {
+        let small = scaling(0.02, 0.02, 0.02);
+        assert_eq!(
+            Transform::try_new(small.clone()).unwrap().inverse(),
+            &scaling(50.0, 50.0, 50.0)
+        );
+        assert!(Transform::try_new(scaling(0.0, 1.0, 1.0)).is_none());
+
+        let t = Transform::new(scaling(0.0, 1.0, 1.0));
+        assert_eq!(t.matrix(), &scaling(0.0, 1.0, 1.0));
+        assert!(t.inverse()[(1, 1)].is_nan());
+    }
+
+    #[test]
+    fn identity_transform() {
+        let t = Transform::default();
+        assert_eq!(t.matrix(), &Mat4::identity());
+        assert_eq!(t.inverse(), &Mat4::identity());
+        assert_eq!(t.inverse_transpose(), &Mat4::identity());
+    }
+
+    #[test]
+    fn motion_interpolates_between_keyframes() {
+        let m = Motion::linear(translation(0.0, 0.0, 0.0), translation(4.0, 0.0, 0.0));
+        assert_eq!(m.at(0.25) * point(0, 0, 0), point(1, 0, 0));
+        assert_eq!(m.at(-1.0), translation(0.0, 0.0, 0.0));
+        assert_eq!(m.at(2.0), translation(4.0, 0.0, 0.0));
+
+        let m = Motion::keyframed(vec![
+            (1.0, scaling(3.0, 3.0, 3.0)),
+            (0.0, Mat4::identity()),
+            (2.0, Mat4::identity()),
+        ]);
+        assert_eq!(m.keyframes()[0].0, 0.0);
+        assert_eq!(m.at(0.5), scaling(2.0, 2.0, 2.0));
+        assert_eq!(m.at(1.5), scaling(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn motion_bounds_cover_all_keyframes() {
+        let m = Motion::linear(translation(0.0, 0.0, 0.0), translation(4.0, 0.0, 0.0))
+            .then(&scaling(2.0, 2.0, 2.0));
+        assert_eq!(m.at(0.5) * point(1, 0, 0), point(4, 0, 0));
+
+        let b = m.bounds(&BoundingBox::new(point(-1, -1, -1), point(1, 1, 1)));
+        assert_eq!(b.min(), point(-2, -2, -2));
+        assert_eq!(b.max(), point(6, 2, 2));
+    }
+
+    #[test]
+    fn nesting_motions() {
+        let inner = Motion::linear(Mat4::identity(), translation(0.0, 2.0, 0.0));
+        let m = inner.within(&scaling(2.0, 2.0, 2.0));
+        assert_eq!(m.at(0.5) * point(0, 0, 0), point(0, 2, 0));
+
+        let outer = Motion::keyframed(vec![
+            (0.0, Mat4::identity()),
+            (0.5, translation(4.0, 0.0, 0.0)),
+        ]);
+        let m = inner.within_motion(&outer);
+        let times: Vec<_> = m.keyframes().iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, [0.0, 0.5, 1.0]);
+        assert_eq!(m.at(0.5) * point(0, 0, 0), point(4, 1, 0));
+        assert_eq!(m.at(1.0) * point(0, 0, 0), point(4, 2, 0));
+    }
 }
@@ -1,12 +1,13 @@
 use std::{
     fmt::Debug,
     ops::{Add, Div, Mul, MulAssign, Neg, Sub},
-    rc::Rc,
+    sync::Arc,
 };
 
 use crate::patterns::Solid;
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tuple {
     x: f64,
     y: f64,
@@ -79,8 +80,8 @@ impl Tuple {
         *self - normal * 2.0 * self.dot(&normal)
     }
 
-    pub fn solid(self) -> Rc<Solid> {
-        Rc::new(Solid::new(self))
+    pub fn solid(self) -> Arc<Solid> {
+        Arc::new(Solid::new(self))
     }
 }
 
@@ -180,8 +181,6 @@ impl FEquals for f64 {
 }
 
 pub mod helpers {
-    use crate::{matrix::helpers::Mat4, patterns::Pattern};
-
     use super::Tuple;
 
     pub fn tuple<F1, F2, F3, F4>(x: F1, y: F2, z: F3, w: F4) -> Tuple
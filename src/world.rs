@@ -1,7 +1,8 @@
-use std::rc::Rc;
+use std::sync::OnceLock;
 
 use crate::{
-    light::PointLight,
+    bvh::Bvh,
+    light::{Light, LightSample, PointLight},
     material::Material,
     ray::{Computations, Intersection, Intersections, Ray},
     shapes::{Shape, Sphere},
@@ -12,78 +13,211 @@ use crate::{
     },
 };
 
+/// How many reflection bounces `color_at` follows before giving up.
+pub const DEFAULT_MAX_DEPTH: usize = 5;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct World {
     objects: Vec<Box<dyn Shape>>,
-    light: Option<PointLight>,
+    lights: Vec<Box<dyn Light>>,
+    max_depth: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bvh: OnceLock<Bvh>,
+    use_bvh: bool,
 }
 
 impl World {
     pub fn new() -> Self {
         World {
             objects: vec![],
-            light: None,
+            lights: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            bvh: OnceLock::new(),
+            use_bvh: true,
         }
     }
     pub fn objetcs(&self) -> &Vec<Box<dyn Shape>> {
         &self.objects
     }
     pub fn objetcs_mut(&mut self) -> &mut Vec<Box<dyn Shape>> {
+        self.bvh = OnceLock::new();
         &mut self.objects
     }
-    pub fn light(&self) -> Option<&PointLight> {
-        self.light.as_ref()
+    pub fn lights(&self) -> &[Box<dyn Light>] {
+        &self.lights
+    }
+    pub fn add_light<L: Light + 'static>(&mut self, light: L) {
+        self.lights.push(Box::new(light));
+    }
+    /// Removes and returns the light at `index`, like `Vec::remove`.
+    pub fn remove_light(&mut self, index: usize) -> Box<dyn Light> {
+        self.lights.remove(index)
+    }
+    pub fn clear_lights(&mut self) {
+        self.lights.clear();
+    }
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+    /// Limits the number of recursive reflection rays spawned per camera ray.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
     }
-    pub fn light_mut(&mut self) -> Option<&mut PointLight> {
-        self.light.as_mut()
+    pub fn use_bvh(&self) -> bool {
+        self.use_bvh
     }
-    pub fn set_light(&mut self, light: Option<PointLight>) {
-        self.light = light;
+    /// Switches between the bounding volume hierarchy (the default) and
+    /// testing every object, which is useful to validate the former.
+    pub fn set_use_bvh(&mut self, use_bvh: bool) {
+        self.use_bvh = use_bvh;
+    }
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        if !self.use_bvh {
+            return self.intersect_brute_force(ray);
+        }
+
+        let bvh = self.bvh.get_or_init(|| {
+            let bounds: Vec<_> = self.objects.iter().map(|o| o.bounds()).collect();
+            Bvh::build(&bounds)
+        });
+
+        let mut xs = Vec::new();
+
+        for i in bvh.candidates(ray) {
+            xs.append(&mut self.objects[i].intersect(ray));
+        }
+
+        xs.sort_by(|a, b| a.t().total_cmp(&b.t()));
+
+        xs
     }
-    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+    /// Tests every object, without using the bounding volume hierarchy.
+    pub fn intersect_brute_force(&self, ray: &Ray) -> Vec<Intersection<'_>> {
         let mut xs = Vec::new();
 
         for obj in &self.objects {
             xs.append(&mut obj.intersect(ray));
         }
 
-        xs.sort_by(|a, b| a.t().partial_cmp(&b.t()).unwrap());
+        xs.sort_by(|a, b| a.t().total_cmp(&b.t()));
 
         xs
     }
 
     pub fn shade_hit(&self, comps: &Computations) -> Tuple {
-        comps.object.material().lighting(
-            comps.object,
-            self.light().unwrap(),
-            comps.point,
-            comps.eyev,
-            comps.normalv,
-            self.is_shadow(comps.over_point),
-        )
+        self.shade_hit_depth(comps, self.max_depth)
+    }
+
+    pub fn shade_hit_depth(&self, comps: &Computations, remaining: usize) -> Tuple {
+        let material = comps.object.material();
+        let color = material
+            .color
+            .at_object_at_time(comps.object, &comps.point, comps.time);
+        let surface = self
+            .lights
+            .iter()
+            .map(|light| {
+                material.lighting_with_color(
+                    color,
+                    light.as_ref(),
+                    comps.point,
+                    comps.eyev,
+                    comps.normalv,
+                    self.intensity_at_time(light.as_ref(), comps.over_point, comps.time),
+                )
+            })
+            .fold(colors::black(), |acc, c| acc + c);
+        let reflected = self.reflected_color(comps, remaining);
+        let refracted = self.refracted_color(comps, remaining);
+
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = comps.schlick();
+            surface + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
     }
 
     pub fn color_at(&self, ray: &Ray) -> Tuple {
+        self.color_at_depth(ray, self.max_depth)
+    }
+
+    /// Like `color_at`, but follows at most `remaining` reflection bounces.
+    pub fn color_at_depth(&self, ray: &Ray, remaining: usize) -> Tuple {
         let xs = self.intersect(ray);
         if let Some(hit) = xs.hit() {
-            let comps = hit.prepare_comps(ray);
-            self.shade_hit(&comps)
+            let comps = hit.prepare_comps_with(ray, &xs);
+            self.shade_hit_depth(&comps, remaining)
         } else {
             colors::black()
         }
     }
 
-    pub fn is_shadow(&self, point: Tuple) -> bool {
-        let v = *self.light().unwrap().position() - point;
-        let distance = v.magnitude();
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize) -> Tuple {
+        let reflective = comps.object.material().reflective;
+        if remaining == 0 || reflective == 0.0 {
+            return colors::black();
+        }
 
-        let direction = v.normalized();
+        let reflect_ray = Ray::new(comps.over_point, comps.reflectv).with_time(comps.time);
+        self.color_at_depth(&reflect_ray, remaining - 1) * reflective
+    }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: usize) -> Tuple {
+        let transparency = comps.object.material().transparency;
+        if remaining == 0 || transparency == 0.0 {
+            return colors::black();
+        }
+
+        // Snell's law, checking for total internal reflection
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot(&comps.normalv);
+        let sin2_t = n_ratio * n_ratio * (1.0 - cos_i * cos_i);
+        if sin2_t > 1.0 {
+            return colors::black();
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+        let refract_ray = Ray::new(comps.under_point, direction).with_time(comps.time);
 
-        let r = Ray::new(point, direction);
+        self.color_at_depth(&refract_ray, remaining - 1) * transparency
+    }
+
+    /// The fraction of `light`'s samples that are visible from `point`.
+    pub fn intensity_at(&self, light: &dyn Light, point: Tuple) -> f64 {
+        self.intensity_at_time(light, point, 0.0)
+    }
+
+    /// Like `intensity_at`, with moving shapes placed as they are at `time`.
+    /// A light without samples doesn't reach the point at all.
+    pub fn intensity_at_time(&self, light: &dyn Light, point: Tuple, time: f64) -> f64 {
+        let samples = light.samples(point);
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let visible = samples
+            .iter()
+            .filter(|sample| !self.is_sample_shadowed(sample, point, time))
+            .count();
+        visible as f64 / samples.len() as f64
+    }
+
+    /// Whether `light` is completely hidden from `point`.
+    pub fn is_shadow(&self, light: &dyn Light, point: Tuple) -> bool {
+        light
+            .samples(point)
+            .iter()
+            .all(|sample| self.is_sample_shadowed(sample, point, 0.0))
+    }
+
+    fn is_sample_shadowed(&self, sample: &LightSample, point: Tuple, time: f64) -> bool {
+        let r = Ray::new(point, sample.direction).with_time(time);
         let intersections = self.intersect(&r);
 
         let h = intersections.hit();
 
-        h.is_some() && h.unwrap().t() < distance
+        h.is_some() && h.unwrap().t() < sample.distance
     }
 }
 
@@ -100,25 +234,31 @@ impl Default for World {
         s2.set_transform(scaling(0.5, 0.5, 0.5));
 
         Self {
-            light: Some(PointLight::new(color(1, 1, 1), point(-10, 10, -10))),
+            lights: vec![Box::new(PointLight::new(
+                color(1, 1, 1),
+                point(-10, 10, -10),
+            ))],
             objects: vec![Box::new(s1), Box::new(s2)],
+            ..Self::new()
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::{
-        light::PointLight,
+        camera::Camera,
+        light::{AreaLight, DirectionalLight, Light, LightSample, PointLight, SpotLight},
         material::Material,
+        patterns::TestPattern,
         ray::{Intersection, Ray},
-        shapes::{Shape, Sphere},
-        transformation::{scaling, translation},
+        shapes::{Cube, Cylinder, Group, Plane, Shape, Sphere},
+        transformation::{scaling, translation, view_transform, PI},
         tuples::{
             helpers::{color, point, vector},
-            FEquals,
+            FEquals, Tuple,
         },
     };
 
@@ -128,7 +268,7 @@ mod tests {
     fn creating_a_world() {
         let w = World::new();
         assert_eq!(w.objetcs().len(), 0);
-        assert_eq!(w.light(), None);
+        assert!(w.lights().is_empty());
     }
 
     #[test]
@@ -145,7 +285,8 @@ mod tests {
         s2.set_transform(scaling(0.5, 0.5, 0.5));
 
         let w = World::default();
-        assert_eq!(w.light().unwrap(), &light);
+        assert_eq!(w.lights().len(), 1);
+        assert_eq!(format!("{:?}", w.lights()[0]), format!("{light:?}"));
         // assert!(w.objetcs().contains(&s1)); //TODO: Lösung finden
         // assert!(w.objetcs().contains(&s2));
     }
@@ -181,9 +322,10 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn shading_intersection_from_inside() {
         let mut w = World::default();
-        w.light = Some(PointLight::new(color(1, 1, 1), point(0, 0.25, 0)));
+        w.lights = vec![Box::new(PointLight::new(color(1, 1, 1), point(0, 0.25, 0)))];
 
         let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
 
@@ -218,6 +360,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn color_ray_behind() {
         let mut w = World::default();
 
@@ -242,7 +385,7 @@ mod tests {
         let w = World::default();
 
         let p = point(0, 10, 0);
-        assert!(!w.is_shadow(p))
+        assert!(!w.is_shadow(&*w.lights()[0], p))
     }
 
     #[test]
@@ -250,7 +393,7 @@ mod tests {
         let w = World::default();
 
         let p = point(10, -10, 10);
-        assert!(w.is_shadow(p))
+        assert!(w.is_shadow(&*w.lights()[0], p))
     }
 
     #[test]
@@ -258,7 +401,7 @@ mod tests {
         let w = World::default();
 
         let p = point(-20, 20, -20);
-        assert!(!w.is_shadow(p))
+        assert!(!w.is_shadow(&*w.lights()[0], p))
     }
 
     #[test]
@@ -266,13 +409,13 @@ mod tests {
         let w = World::default();
 
         let p = point(-2, 2, -2);
-        assert!(!w.is_shadow(p))
+        assert!(!w.is_shadow(&*w.lights()[0], p))
     }
 
     #[test]
     fn shade_hit_in_shadow() {
         let mut w = World::new();
-        w.set_light(Some(PointLight::new(color(1, 1, 1), point(0, 0, -10))));
+        w.add_light(PointLight::new(color(1, 1, 1), point(0, 0, -10)));
         w.objetcs_mut().push(Box::new(Sphere::new()));
         let mut s2 = Sphere::new();
         s2.set_transform(translation(0.0, 0.0, 10.0));
@@ -284,4 +427,497 @@ mod tests {
         let c = w.shade_hit(&comps);
         assert_eq!(c, color(0.1, 0.1, 0.1));
     }
+
+    #[test]
+    fn point_light_intensity_at() {
+        let w = World::default();
+        let light = &*w.lights()[0];
+        let cases = [
+            (point(0, 1.0001, 0), 1.0),
+            (point(-1.0001, 0, 0), 1.0),
+            (point(0, 0, -1.0001), 1.0),
+            (point(0, 0, 1.0001), 0.0),
+            (point(1.0001, 0, 0), 0.0),
+            (point(0, -1.0001, 0), 0.0),
+            (point(0, 0, 0), 0.0),
+        ];
+        for (p, expected) in cases {
+            assert!(w.intensity_at(light, p).eps_eq(expected), "{p:?}");
+        }
+    }
+
+    #[test]
+    fn area_light_intensity_at() {
+        let w = World::default();
+        let light = AreaLight::new(
+            color(1, 1, 1),
+            point(-0.5, -0.5, -5),
+            vector(1, 0, 0),
+            2,
+            vector(0, 1, 0),
+            2,
+        );
+        let cases = [
+            (point(0, 0, 2), 0.0),
+            (point(1, -1, 2), 0.25),
+            (point(1.5, 0, 2), 0.5),
+            (point(1.25, 1.25, 3), 0.75),
+            (point(0, 0, -2), 1.0),
+        ];
+        for (p, expected) in cases {
+            assert!(w.intensity_at(&light, p).eps_eq(expected), "{p:?}");
+        }
+    }
+
+    #[test]
+    fn light_without_samples() {
+        #[derive(Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        struct Unlit;
+
+        #[cfg_attr(feature = "serde", typetag::serde)]
+        impl Light for Unlit {
+            fn intensity_at(&self, _: Tuple) -> Tuple {
+                color(1, 1, 1)
+            }
+            fn samples(&self, _: Tuple) -> Vec<LightSample> {
+                Vec::new()
+            }
+        }
+
+        let mut w = World::default();
+        assert_eq!(w.intensity_at(&Unlit, point(0, 0, -2)), 0.0);
+
+        w.clear_lights();
+        w.add_light(Unlit);
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        assert_eq!(w.color_at(&r), color(0.08, 0.1, 0.06));
+    }
+
+    #[test]
+    fn jittered_area_light_is_reproducible() {
+        let mut w = World::default();
+        w.clear_lights();
+        w.add_light(
+            AreaLight::new(
+                color(1, 1, 1),
+                point(-1, 2, -3),
+                vector(2, 0, 0),
+                4,
+                vector(0, 0, 2),
+                4,
+            )
+            .jittered(11),
+        );
+        let mut floor = Plane::new();
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        w.objetcs_mut().push(Box::new(floor));
+
+        let mut c = Camera::new_transformed(
+            16,
+            12,
+            PI / 3.0,
+            view_transform(point(0, 2, -6), point(0, -1, 0), vector(0, 1, 0)),
+        );
+        c.set_threads(1);
+        let serial = c.render(&w);
+        c.set_threads(3);
+        let parallel = c.render(&w);
+        for y in 0..12 {
+            for x in 0..16 {
+                assert_eq!(serial[(x, y)], parallel[(x, y)]);
+            }
+        }
+    }
+
+    #[test]
+    fn directional_shadow_has_no_distance_limit() {
+        let mut w = World::new();
+        let mut far = Sphere::new();
+        far.set_transform(translation(0.0, 1000.0, 0.0) * scaling(5.0, 5.0, 5.0));
+        w.objetcs_mut().push(Box::new(far));
+        let sun = DirectionalLight::new(color(1, 1, 1), vector(0, -1, 0));
+        let lamp = PointLight::new(color(1, 1, 1), point(0, 10, 0));
+
+        assert!(w.is_shadow(&sun, point(0, 0, 0)));
+        assert!(!w.is_shadow(&lamp, point(0, 0, 0)));
+        assert!(!w.is_shadow(&sun, point(10, 0, 0)));
+    }
+
+    #[test]
+    fn shade_hit_with_spot_light() {
+        let shade = |spot_direction| {
+            let mut w = World::default();
+            w.clear_lights();
+            w.add_light(SpotLight::new(
+                color(1, 1, 1),
+                point(0, 0, -10),
+                spot_direction,
+                PI / 16.0,
+                PI / 8.0,
+            ));
+            let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+            let i = Intersection::new(4.0, w.objetcs()[0].as_ref());
+            w.shade_hit(&i.prepare_comps(&r))
+        };
+
+        assert_eq!(shade(vector(0, 0, 1)), color(0.84, 1.0, 0.68));
+        assert_eq!(shade(vector(0, 1, 0)), color(0, 0, 0));
+    }
+
+    #[test]
+    fn adding_and_removing_lights() {
+        let mut w = World::default();
+        let fill = PointLight::new(color(0.5, 0.5, 0.5), point(10, 10, -10));
+        w.add_light(fill);
+        assert_eq!(w.lights().len(), 2);
+        assert_eq!(format!("{:?}", w.remove_light(1)), format!("{fill:?}"));
+        assert_eq!(w.lights().len(), 1);
+        w.clear_lights();
+        assert!(w.lights().is_empty());
+    }
+
+    #[test]
+    fn shade_hit_sums_lights() {
+        let mut w = World::default();
+        w.add_light(PointLight::new(color(1, 1, 1), point(-10, 10, -10)));
+
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let i = Intersection::new(4.0, w.objetcs()[0].as_ref());
+        let comps = i.prepare_comps(&r);
+        assert_eq!(w.shade_hit(&comps), color(0.76132, 0.95166, 0.5710));
+    }
+
+    #[test]
+    fn shade_hit_shadow_per_light() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(color(1, 1, 1), point(0, 0, -10)));
+        w.add_light(PointLight::new(color(1, 1, 1), point(0, 0, 7)));
+        w.objetcs_mut().push(Box::new(Sphere::new()));
+        let mut s2 = Sphere::new();
+        s2.set_transform(translation(0.0, 0.0, 10.0));
+        w.objetcs_mut().push(Box::new(s2));
+
+        // Only the light between the spheres reaches the hit.
+        let r = Ray::new(point(0, 0, 5), vector(0, 0, 1));
+        let i = Intersection::new(4.0, w.objetcs()[1].as_ref());
+        let comps = i.prepare_comps(&r);
+        assert!(w.is_shadow(&*w.lights()[0], comps.over_point));
+        assert!(!w.is_shadow(&*w.lights()[1], comps.over_point));
+        assert_eq!(
+            w.shade_hit(&comps),
+            color(0.1, 0.1, 0.1) + color(1.9, 1.9, 1.9)
+        );
+    }
+
+    #[test]
+    fn no_lights_is_black() {
+        let mut w = World::default();
+        w.clear_lights();
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        assert_eq!(w.color_at(&r), color(0, 0, 0));
+    }
+
+    #[test]
+    fn reflected_color_nonreflective() {
+        let mut w = World::default();
+        w.objetcs_mut()[1].material_mut().ambient = 1.0;
+
+        let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
+        let shape = w.objetcs()[1].as_ref();
+        let i = Intersection::new(1.0, shape);
+        let comps = i.prepare_comps(&r);
+
+        assert_eq!(w.reflected_color(&comps, 5), color(0, 0, 0));
+    }
+
+    fn reflective_plane() -> Plane {
+        let mut shape = Plane::new();
+        shape.material_mut().reflective = 0.5;
+        shape.set_transform(translation(0.0, -1.0, 0.0));
+        shape
+    }
+
+    #[test]
+    fn reflected_color_reflective() {
+        let mut w = World::default();
+        w.objetcs_mut().push(Box::new(reflective_plane()));
+
+        let r = Ray::new(
+            point(0, 0, -3),
+            vector(0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let i = Intersection::new(2.0_f64.sqrt(), w.objetcs()[2].as_ref());
+        let comps = i.prepare_comps(&r);
+
+        assert_eq!(
+            w.reflected_color(&comps, 5),
+            color(0.19033, 0.23792, 0.14275)
+        );
+    }
+
+    #[test]
+    fn shade_hit_reflective() {
+        let mut w = World::default();
+        w.objetcs_mut().push(Box::new(reflective_plane()));
+
+        let r = Ray::new(
+            point(0, 0, -3),
+            vector(0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let i = Intersection::new(2.0_f64.sqrt(), w.objetcs()[2].as_ref());
+        let comps = i.prepare_comps(&r);
+
+        assert_eq!(w.shade_hit(&comps), color(0.87676, 0.92434, 0.82917));
+    }
+
+    #[test]
+    fn mutually_reflective_surfaces() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(color(1, 1, 1), point(0, 0, 0)));
+
+        let mut lower = Plane::new();
+        lower.material_mut().reflective = 1.0;
+        lower.set_transform(translation(0.0, -1.0, 0.0));
+        w.objetcs_mut().push(Box::new(lower));
+
+        let mut upper = Plane::new();
+        upper.material_mut().reflective = 1.0;
+        upper.set_transform(translation(0.0, 1.0, 0.0));
+        w.objetcs_mut().push(Box::new(upper));
+
+        let r = Ray::new(point(0, 0, 0), vector(0, 1, 0));
+        // terminates instead of overflowing the stack
+        w.color_at(&r);
+    }
+
+    #[test]
+    fn reflected_color_at_max_depth() {
+        let mut w = World::default();
+        w.objetcs_mut().push(Box::new(reflective_plane()));
+
+        let r = Ray::new(
+            point(0, 0, -3),
+            vector(0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let i = Intersection::new(2.0_f64.sqrt(), w.objetcs()[2].as_ref());
+        let comps = i.prepare_comps(&r);
+
+        assert_eq!(w.reflected_color(&comps, 0), color(0, 0, 0));
+    }
+
+    #[test]
+    fn refracted_color_opaque() {
+        let w = World::default();
+        let shape = w.objetcs()[0].as_ref();
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let xs = vec![Intersection::new(4.0, shape), Intersection::new(6.0, shape)];
+
+        let comps = xs[0].prepare_comps_with(&r, &xs);
+        assert_eq!(w.refracted_color(&comps, 5), color(0, 0, 0));
+    }
+
+    #[test]
+    fn refracted_color_at_max_depth() {
+        let mut w = World::default();
+        w.objetcs_mut()[0].material_mut().transparency = 1.0;
+        w.objetcs_mut()[0].material_mut().refractive_index = 1.5;
+        let shape = w.objetcs()[0].as_ref();
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let xs = vec![Intersection::new(4.0, shape), Intersection::new(6.0, shape)];
+
+        let comps = xs[0].prepare_comps_with(&r, &xs);
+        assert_eq!(w.refracted_color(&comps, 0), color(0, 0, 0));
+    }
+
+    #[test]
+    fn refracted_color_total_internal_reflection() {
+        let mut w = World::default();
+        w.objetcs_mut()[0].material_mut().transparency = 1.0;
+        w.objetcs_mut()[0].material_mut().refractive_index = 1.5;
+        let shape = w.objetcs()[0].as_ref();
+        let r = Ray::new(point(0, 0, 2.0_f64.sqrt() / 2.0), vector(0, 1, 0));
+        let xs = vec![
+            Intersection::new(-2.0_f64.sqrt() / 2.0, shape),
+            Intersection::new(2.0_f64.sqrt() / 2.0, shape),
+        ];
+
+        let comps = xs[1].prepare_comps_with(&r, &xs);
+        assert_eq!(w.refracted_color(&comps, 5), color(0, 0, 0));
+    }
+
+    #[test]
+    fn refracted_color_with_refracted_ray() {
+        let mut w = World::default();
+        {
+            let a = w.objetcs_mut()[0].material_mut();
+            a.ambient = 1.0;
+            a.color = Arc::new(TestPattern::new());
+        }
+        {
+            let b = w.objetcs_mut()[1].material_mut();
+            b.transparency = 1.0;
+            b.refractive_index = 1.5;
+        }
+        let a = w.objetcs()[0].as_ref();
+        let b = w.objetcs()[1].as_ref();
+        let r = Ray::new(point(0, 0, 0.1), vector(0, 1, 0));
+        let xs = vec![
+            Intersection::new(-0.9899, a),
+            Intersection::new(-0.4899, b),
+            Intersection::new(0.4899, b),
+            Intersection::new(0.9899, a),
+        ];
+
+        let comps = xs[2].prepare_comps_with(&r, &xs);
+        assert_eq!(w.refracted_color(&comps, 5), color(0, 0.99888, 0.04722));
+    }
+
+    #[test]
+    fn shade_hit_transparent() {
+        let mut w = World::default();
+
+        let mut floor = Plane::new();
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        floor.material_mut().transparency = 0.5;
+        floor.material_mut().refractive_index = 1.5;
+        w.objetcs_mut().push(Box::new(floor));
+
+        let mut ball = Sphere::new();
+        ball.material_mut().color = color(1, 0, 0).solid();
+        ball.material_mut().ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5));
+        w.objetcs_mut().push(Box::new(ball));
+
+        let r = Ray::new(
+            point(0, 0, -3),
+            vector(0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let xs = vec![Intersection::new(2.0_f64.sqrt(), w.objetcs()[2].as_ref())];
+
+        let comps = xs[0].prepare_comps_with(&r, &xs);
+        assert_eq!(w.shade_hit(&comps), color(0.93642, 0.68642, 0.68642));
+    }
+
+    #[test]
+    fn shade_hit_reflective_transparent() {
+        let mut w = World::default();
+
+        let mut floor = Plane::new();
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        floor.material_mut().reflective = 0.5;
+        floor.material_mut().transparency = 0.5;
+        floor.material_mut().refractive_index = 1.5;
+        w.objetcs_mut().push(Box::new(floor));
+
+        let mut ball = Sphere::new();
+        ball.material_mut().color = color(1, 0, 0).solid();
+        ball.material_mut().ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5));
+        w.objetcs_mut().push(Box::new(ball));
+
+        let r = Ray::new(
+            point(0, 0, -3),
+            vector(0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let xs = vec![Intersection::new(2.0_f64.sqrt(), w.objetcs()[2].as_ref())];
+
+        let comps = xs[0].prepare_comps_with(&r, &xs);
+        assert_eq!(w.shade_hit(&comps), color(0.93391, 0.69643, 0.69243));
+    }
+
+    #[test]
+    fn bvh_render_matches_brute_force() {
+        let mut w = World::default();
+        let mut floor = Plane::new();
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        floor.material_mut().reflective = 0.5;
+        w.objetcs_mut().push(Box::new(floor));
+        for i in 0..30 {
+            let mut s = if i % 3 == 0 {
+                Sphere::glass()
+            } else {
+                Sphere::new()
+            };
+            s.set_transform(
+                translation(
+                    (i % 6) as f64 - 2.5,
+                    (i / 6) as f64 * 0.4,
+                    2.0 + i as f64 * 0.1,
+                ) * scaling(0.3, 0.3, 0.3),
+            );
+            w.objetcs_mut().push(Box::new(s));
+        }
+        let table = Group::new()
+            .with_child(Cube::new())
+            .with_child(Cylinder::truncated(0.0, 2.0, true));
+        w.objetcs_mut().push(Box::new(table));
+
+        let mut c = Camera::new_transformed(
+            24,
+            16,
+            PI / 3.0,
+            view_transform(point(0, 1.5, -6), point(0, 0, 2), vector(0, 1, 0)),
+        );
+        c.set_threads(1);
+        let fast = c.render(&w);
+        w.set_use_bvh(false);
+        let slow = c.render(&w);
+
+        for y in 0..16 {
+            for x in 0..24 {
+                let (a, b) = (fast[(x, y)], slow[(x, y)]);
+                assert_eq!(
+                    (a.r().to_bits(), a.g().to_bits(), a.b().to_bits()),
+                    (b.r().to_bits(), b.g().to_bits(), b.b().to_bits())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bvh_matches_brute_force_for_near_axis_rays() {
+        let mut w = World::new();
+        for (x, y, z) in [(20000, 0, 0), (0, 20000, 0), (0, 0, -20000), (3, 0, 0)] {
+            let mut s = Sphere::new();
+            s.set_transform(translation(x as f64, y as f64, z as f64));
+            w.objetcs_mut().push(Box::new(s));
+        }
+
+        let rays = [
+            Ray::new(point(0, -1.05, 0), vector(1, 5e-6, 0)),
+            Ray::new(point(0, 1.0, 0), vector(1, -1e-7, 0)),
+            Ray::new(point(-1.05, 0, 0), vector(3e-6, 1, 0)),
+            Ray::new(point(0, 0.999, 0), vector(0, 1e-9, -1)),
+            Ray::new(point(0, 0, 0), vector(1, 0, 0)),
+        ];
+        for r in rays {
+            let ts = |xs: Vec<Intersection>| xs.iter().map(|i| i.t()).collect::<Vec<_>>();
+            let fast = ts(w.intersect(&r));
+            assert_eq!(fast, ts(w.intersect_brute_force(&r)), "{r:?}");
+            assert!(!fast.is_empty(), "{r:?}");
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn worlds_round_trip_through_json() {
+        let mut w = World::default();
+        w.add_light(AreaLight::new(
+            color(0.5, 0.5, 0.5),
+            point(-1, 2, 4),
+            vector(2, 0, 0),
+            4,
+            vector(0, 2, 0),
+            2,
+        ));
+
+        let json = serde_json::to_string_pretty(&w).unwrap();
+        let loaded: World = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string_pretty(&loaded).unwrap(), json);
+        assert_eq!(loaded.lights().len(), 2);
+
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        assert_eq!(loaded.color_at(&r), w.color_at(&r));
+    }
 }